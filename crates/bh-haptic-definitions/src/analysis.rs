@@ -0,0 +1,210 @@
+use derivative::Derivative;
+use getset::Getters;
+
+use crate::{
+  DevicePosition, HapticDefinitionsMessage, HapticTimeline, PositionTimeline, RenderOptions,
+  TactFileProject,
+};
+
+/// Metrics of a single motor over the whole pattern.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MotorAnalysis {
+  index: usize,
+  peak_intensity: f64,
+  /// Root mean square of the intensities of all frames, including the silent ones.
+  rms_intensity: f64,
+  active_millis: u32,
+}
+
+/// Metrics of a single device of the pattern.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PositionAnalysis {
+  position: DevicePosition,
+  /// Time any motor of the device is playing.
+  active_millis: u32,
+  peak_intensity: f64,
+  /// Number of the motors playing at any time.
+  motors_touched: usize,
+  /// Highest number of the motors playing at the same time.
+  max_simultaneous_motors: usize,
+  motors: Vec<MotorAnalysis>,
+}
+
+impl PositionAnalysis {
+  fn new(position: DevicePosition, timeline: &PositionTimeline, pattern: &HapticTimeline) -> Self {
+    let frame_count = timeline.frames().len();
+    // the last frame is cut short by the end of the pattern
+    let frame_millis = |tick: usize| {
+      let start = (tick as u32).saturating_mul(*pattern.tick_millis());
+      (*pattern.tick_millis()).min(pattern.duration_millis().saturating_sub(start))
+    };
+
+    let motors = (0..*timeline.motor_count())
+      .map(|motor| {
+        let intensities = (0..frame_count).map(|tick| timeline.intensity(tick, motor));
+        let square_sum = intensities
+          .clone()
+          .map(|intensity| intensity * intensity)
+          .sum::<f64>();
+
+        MotorAnalysis {
+          index: motor,
+          peak_intensity: intensities.clone().fold(0.0, f64::max),
+          rms_intensity: match frame_count {
+            0 => 0.0,
+            frame_count => (square_sum / frame_count as f64).sqrt(),
+          },
+          active_millis: (0..frame_count)
+            .filter(|tick| timeline.intensity(*tick, motor) > 0.0)
+            .map(frame_millis)
+            .sum(),
+        }
+      })
+      .collect::<Vec<_>>();
+
+    let simultaneous_motors = timeline
+      .frames()
+      .iter()
+      .map(|frame| frame.iter().filter(|intensity| **intensity > 0.0).count())
+      .collect::<Vec<_>>();
+
+    Self {
+      position,
+      active_millis: (0..frame_count)
+        .filter(|tick| simultaneous_motors[*tick] > 0)
+        .map(frame_millis)
+        .sum(),
+      peak_intensity: motors
+        .iter()
+        .map(|motor| motor.peak_intensity)
+        .fold(0.0, f64::max),
+      motors_touched: motors
+        .iter()
+        .filter(|motor| motor.peak_intensity > 0.0)
+        .count(),
+      max_simultaneous_motors: simultaneous_motors.into_iter().max().unwrap_or(0),
+      motors,
+    }
+  }
+}
+
+/// Metrics of a rendered pattern, see [HapticTimeline::analyze].
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PatternAnalysis {
+  duration_millis: u32,
+  peak_intensity: f64,
+  /// Number of the motors playing at any time, over all devices.
+  motors_touched: usize,
+  /// Highest number of the motors playing at the same time, over all devices.
+  max_simultaneous_motors: usize,
+  /// Devices sorted by their name.
+  positions: Vec<PositionAnalysis>,
+}
+
+impl PatternAnalysis {
+  /// Whether no motor ever plays.
+  pub fn is_silent(&self) -> bool {
+    self.motors_touched == 0
+  }
+}
+
+/// Metrics of a mapping, see [HapticDefinitionsMessage::analyze].
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MappingAnalysis {
+  key: String,
+  event_time: u32,
+  pattern: PatternAnalysis,
+}
+
+/// Metrics of every mapping of the definitions, in their order.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct HapticDefinitionsAnalysis {
+  tick_millis: u32,
+  mappings: Vec<MappingAnalysis>,
+}
+
+impl HapticTimeline {
+  pub fn analyze(&self) -> PatternAnalysis {
+    let mut positions = self
+      .positions()
+      .iter()
+      .map(|(position, timeline)| PositionAnalysis::new(position.clone(), timeline, self))
+      .collect::<Vec<_>>();
+    positions.sort_by_key(|analysis| analysis.position.to_string());
+
+    let max_simultaneous_motors = (0..self.frame_count())
+      .map(|tick| {
+        self
+          .positions()
+          .values()
+          .filter_map(|timeline| timeline.frame(tick))
+          .flatten()
+          .filter(|intensity| **intensity > 0.0)
+          .count()
+      })
+      .max()
+      .unwrap_or(0);
+
+    PatternAnalysis {
+      duration_millis: *self.duration_millis(),
+      peak_intensity: positions
+        .iter()
+        .map(|position| position.peak_intensity)
+        .fold(0.0, f64::max),
+      motors_touched: positions
+        .iter()
+        .map(|position| position.motors_touched)
+        .sum(),
+      max_simultaneous_motors,
+      positions,
+    }
+  }
+}
+
+impl TactFileProject {
+  /// Renders the project and analyzes the timeline.
+  pub fn analyze(&self, options: &RenderOptions) -> PatternAnalysis {
+    self.render(options).analyze()
+  }
+}
+
+impl HapticDefinitionsMessage {
+  /// Renders every mapping and analyzes its timeline, the mappings played from their audio clips
+  /// only are silent.
+  pub fn analyze(&self, options: &RenderOptions) -> HapticDefinitionsAnalysis {
+    let mappings = self
+      .haptic_mappings()
+      .iter()
+      .map(|mapping| MappingAnalysis {
+        key: mapping.key().clone(),
+        event_time: *mapping.event_time(),
+        pattern: mapping.render(options).analyze(),
+      })
+      .collect();
+
+    HapticDefinitionsAnalysis {
+      tick_millis: *options.tick_millis(),
+      mappings,
+    }
+  }
+}
@@ -0,0 +1,119 @@
+use derivative::Derivative;
+use getset::Getters;
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use crate::ExtraFields;
+use crate::{DevicePosition, PatternPosition};
+
+/// Pattern of a mapping generated from an audio clip by the Designer.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct HapticDefinitionAudioFilePattern {
+  /// Id of the Designer pattern the clip was converted into, empty for unsaved conversions.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pattern_id: String,
+  #[cfg_attr(feature = "serde", serde(default))]
+  snapshot_id: String,
+  #[getset(skip)]
+  #[cfg_attr(feature = "serde", serde(with = "crate::tact::pattern_position"))]
+  position: PatternPosition,
+  clip: AudioClip,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
+}
+
+/// Audio clip rendered into motor frames, one every [crate::default_tick_millis].
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AudioClip {
+  #[cfg_attr(feature = "serde", serde(default))]
+  id: String,
+  #[cfg_attr(feature = "serde", serde(default))]
+  name: String,
+  /// `-1` for clips which were never uploaded.
+  #[cfg_attr(feature = "serde", serde(default))]
+  version: i64,
+  /// As reported by the Designer, does not always match the number of frames.
+  #[cfg_attr(feature = "serde", serde(default))]
+  duration: u32,
+  /// Base64 encoded frames of every position, a byte per motor with intensities in `0..=100`.
+  #[cfg_attr(feature = "serde", serde(default))]
+  patterns: HashMap<DevicePosition, Vec<String>>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
+}
+
+impl HapticDefinitionAudioFilePattern {
+  pub fn new(position: DevicePosition, clip: AudioClip) -> Self {
+    Self {
+      pattern_id: String::new(),
+      snapshot_id: String::new(),
+      position: position.into(),
+      clip,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  pub fn position(&self) -> &DevicePosition {
+    &self.position.position
+  }
+
+  #[cfg(feature = "serde")]
+  pub fn extra(&self) -> &ExtraFields {
+    &self.extra
+  }
+}
+
+impl AudioClip {
+  pub fn new(name: String, duration: u32, patterns: HashMap<DevicePosition, Vec<String>>) -> Self {
+    Self {
+      id: String::new(),
+      name,
+      version: -1,
+      duration,
+      patterns,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  #[cfg(feature = "serde")]
+  pub fn extra(&self) -> &ExtraFields {
+    &self.extra
+  }
+
+  /// Decoded frames of the position, empty if the clip does not cover it.
+  #[cfg(feature = "serde")]
+  pub fn frames(&self, position: &DevicePosition) -> anyhow::Result<Vec<Vec<u8>>> {
+    use anyhow::Context;
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    let Some(frames) = self.patterns.get(position) else {
+      return Ok(vec![]);
+    };
+
+    frames
+      .iter()
+      .enumerate()
+      .map(|(i, frame)| {
+        STANDARD
+          .decode(frame)
+          .with_context(|| format!("Invalid frame {i} of {position}"))
+      })
+      .collect()
+  }
+}
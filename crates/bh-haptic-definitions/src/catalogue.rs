@@ -0,0 +1,243 @@
+//! Canonical motor layouts of the bHaptics devices, as the Designer embeds them into `.tact`
+//! files. Coordinates are normalized to `0.0..=1.0` within every position, indices are
+//! row-major starting from the top left motor.
+
+use std::str::FromStr;
+
+use crate::{DevicePosition, DeviceType, Layout, LayoutPoint};
+
+impl DeviceType {
+  /// Positions covered by the device, e.g. both sides of a vest or both forearms.
+  pub fn positions(&self) -> Vec<DevicePosition> {
+    match self {
+      DeviceType::Tactot
+      | DeviceType::Tactot2
+      | DeviceType::Tactot3
+      | DeviceType::TactSuitX40
+      | DeviceType::TactSuitX16
+      | DeviceType::TactSuitPro
+      | DeviceType::TactSuitAir => vec![DevicePosition::VestFront, DevicePosition::VestBack],
+      DeviceType::Tactosy | DeviceType::Tactosy2 => {
+        vec![DevicePosition::ForearmL, DevicePosition::ForearmR]
+      }
+      DeviceType::TactosyH | DeviceType::Hand => vec![DevicePosition::HandL, DevicePosition::HandR],
+      DeviceType::TactosyF | DeviceType::Foot => vec![DevicePosition::FootL, DevicePosition::FootR],
+      DeviceType::TactVisor | DeviceType::TactFacial | DeviceType::Tactal => {
+        vec![DevicePosition::Head]
+      }
+      DeviceType::TactGloveL => vec![DevicePosition::GloveL],
+      DeviceType::TactGloveR => vec![DevicePosition::GloveR],
+    }
+  }
+
+  /// Motors of the device at the position, `None` if the device does not cover it.
+  pub fn layout_points(&self, position: &DevicePosition) -> Option<Vec<LayoutPoint>> {
+    if !self.positions().contains(position) {
+      return None;
+    }
+
+    let points = match self {
+      DeviceType::Tactot | DeviceType::Tactot2 | DeviceType::Tactot3 | DeviceType::TactSuitX40 => {
+        grid(4, 5)
+      }
+      DeviceType::TactSuitPro => grid(4, 4),
+      DeviceType::TactSuitX16 | DeviceType::TactSuitAir => grid(2, 4),
+      DeviceType::Tactosy | DeviceType::Tactosy2 => grid(3, 2),
+      DeviceType::TactosyH | DeviceType::Hand => column(3),
+      DeviceType::TactosyF | DeviceType::Foot => row(&[0.0, 0.5, 1.0]),
+      DeviceType::TactVisor => row(&[0.0, 0.3, 0.7, 1.0]),
+      DeviceType::TactFacial | DeviceType::Tactal => row(&[0.0, 0.2, 0.4, 0.6, 0.8, 1.0]),
+      // fingertips from the thumb, then the wrist
+      DeviceType::TactGloveL | DeviceType::TactGloveR => (0..5)
+        .map(|finger| LayoutPoint::new(finger, normalized(finger, 5), 0.0))
+        .chain([LayoutPoint::new(5, 0.5, 1.0)])
+        .collect(),
+    };
+
+    Some(points)
+  }
+
+  pub fn motor_count(&self, position: &DevicePosition) -> Option<usize> {
+    self.layout_points(position).map(|points| points.len())
+  }
+
+  /// Layout with the points of every position of the device.
+  pub fn layout(&self) -> Layout {
+    let layouts = self
+      .positions()
+      .into_iter()
+      .filter_map(|position| Some((position.clone(), self.layout_points(&position)?)))
+      .collect();
+
+    Layout::new(self.to_string(), self.to_string(), Some(layouts))
+  }
+}
+
+impl DevicePosition {
+  /// Device the Designer assumes for patterns of the position.
+  pub fn default_device_type(&self) -> Option<DeviceType> {
+    match self {
+      DevicePosition::Vest | DevicePosition::VestFront | DevicePosition::VestBack => {
+        Some(DeviceType::TactSuitX40)
+      }
+      DevicePosition::Head | DevicePosition::Tactal => Some(DeviceType::Tactal),
+      DevicePosition::ForearmL | DevicePosition::ForearmR => Some(DeviceType::Tactosy2),
+      DevicePosition::HandL | DevicePosition::HandR => Some(DeviceType::TactosyH),
+      DevicePosition::FootL | DevicePosition::FootR => Some(DeviceType::TactosyF),
+      DevicePosition::GloveL => Some(DeviceType::TactGloveL),
+      DevicePosition::GloveR => Some(DeviceType::TactGloveR),
+      DevicePosition::Unknown(_) => None,
+    }
+  }
+
+  /// Motors of the [DevicePosition::default_device_type] at this position.
+  pub fn default_layout_points(&self) -> Option<Vec<LayoutPoint>> {
+    let position = match self {
+      // the whole vest is addressed as its front in the layouts
+      DevicePosition::Vest => &DevicePosition::VestFront,
+      DevicePosition::Tactal => &DevicePosition::Head,
+      other => other,
+    };
+
+    self.default_device_type()?.layout_points(position)
+  }
+}
+
+impl Layout {
+  /// Layout with the positions missing in `layouts` filled from the catalogue.
+  ///
+  /// The device is taken from the layout `type` if it is known, otherwise from
+  /// [DevicePosition::default_device_type].
+  pub fn with_default_points(&self, positions: impl IntoIterator<Item = DevicePosition>) -> Layout {
+    let device_type = DeviceType::from_str(self.r#type()).ok();
+    let mut layouts = self.layouts().clone().unwrap_or_default();
+
+    for position in positions {
+      if layouts.contains_key(&position) {
+        continue;
+      }
+
+      let points = device_type
+        .as_ref()
+        .and_then(|device_type| device_type.layout_points(&position))
+        .or_else(|| position.default_layout_points());
+      if let Some(points) = points {
+        layouts.insert(position, points);
+      }
+    }
+
+    self.clone().with_layouts(Some(layouts))
+  }
+}
+
+/// `columns` x `rows` motors spread evenly over the whole position.
+fn grid(columns: u32, rows: u32) -> Vec<LayoutPoint> {
+  (0..rows)
+    .flat_map(|row| {
+      (0..columns).map(move |column| {
+        LayoutPoint::new(
+          row * columns + column,
+          normalized(column, columns),
+          normalized(row, rows),
+        )
+      })
+    })
+    .collect()
+}
+
+fn row(xs: &[f64]) -> Vec<LayoutPoint> {
+  xs.iter()
+    .enumerate()
+    .map(|(index, x)| LayoutPoint::new(index as u32, *x, 0.5))
+    .collect()
+}
+
+fn column(rows: u32) -> Vec<LayoutPoint> {
+  (0..rows)
+    .map(|row| LayoutPoint::new(row, 0.5, normalized(row, rows)))
+    .collect()
+}
+
+/// Position of the `i`-th of `count` motors, rounded the way the Designer does (`0.333`).
+fn normalized(i: u32, count: u32) -> f64 {
+  if count <= 1 {
+    return 0.5;
+  }
+  (f64::from(i) / f64::from(count - 1) * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn vest_grid_matches_the_designer() {
+    let points = DeviceType::TactSuitX40
+      .layout_points(&DevicePosition::VestBack)
+      .unwrap();
+
+    assert_eq!(points.len(), 20);
+    assert_eq!(points[1], LayoutPoint::new(1, 0.333, 0.0));
+    assert_eq!(points[6], LayoutPoint::new(6, 0.667, 0.25));
+    assert_eq!(points[19], LayoutPoint::new(19, 1.0, 1.0));
+  }
+
+  #[test]
+  fn every_device_has_motors_at_its_positions() {
+    let device_types = [
+      DeviceType::Tactosy,
+      DeviceType::Tactosy2,
+      DeviceType::TactosyH,
+      DeviceType::TactosyF,
+      DeviceType::TactVisor,
+      DeviceType::TactFacial,
+      DeviceType::Tactal,
+      DeviceType::Tactot,
+      DeviceType::Tactot2,
+      DeviceType::Tactot3,
+      DeviceType::TactSuitX40,
+      DeviceType::TactSuitX16,
+      DeviceType::TactSuitPro,
+      DeviceType::TactSuitAir,
+      DeviceType::TactGloveL,
+      DeviceType::TactGloveR,
+      DeviceType::Hand,
+      DeviceType::Foot,
+    ];
+
+    for device_type in device_types {
+      for position in device_type.positions() {
+        let points = device_type.layout_points(&position).unwrap();
+        assert!(!points.is_empty(), "{device_type} {position}");
+        assert!(
+          points
+            .iter()
+            .enumerate()
+            .all(|(i, point)| *point.index() as usize == i
+              && (0.0..=1.0).contains(point.x())
+              && (0.0..=1.0).contains(point.y())),
+          "{device_type} {position}"
+        );
+      }
+    }
+
+    assert_eq!(
+      DeviceType::TactSuitX16.motor_count(&DevicePosition::VestFront),
+      Some(8)
+    );
+    assert_eq!(
+      DeviceType::TactSuitX16.motor_count(&DevicePosition::ForearmL),
+      None
+    );
+  }
+
+  #[test]
+  fn missing_layouts_are_filled_from_the_catalogue() {
+    let layout = Layout::new("Tactot".to_string(), "Tactot".to_string(), None)
+      .with_default_points([DevicePosition::VestFront, DevicePosition::ForearmL]);
+
+    assert_eq!(layout.motor_count(&DevicePosition::VestFront), Some(20));
+    assert_eq!(layout.motor_count(&DevicePosition::ForearmL), Some(6));
+    assert_eq!(layout.motor_count(&DevicePosition::VestBack), None);
+  }
+}
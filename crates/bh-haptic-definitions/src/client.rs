@@ -0,0 +1,370 @@
+use anyhow::Context;
+use derivative::Derivative;
+use getset::{Getters, WithSetters};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::*;
+
+use crate::{HapticDefinitionsMessage, SdkApiResponseV3, is_valid_path_segment};
+
+pub const DEFAULT_DEFINITIONS_BASE_URL: &str = "https://sdk-apis.bhaptics.com";
+
+/// Failure to fetch the haptic definitions of an application.
+#[derive(Debug, thiserror::Error)]
+pub enum HapticDefinitionsError {
+  #[error("Invalid app id or API key ({code}): {message}")]
+  InvalidCredentials { code: i64, message: String },
+  #[error("No deployed workspace ({code}): {message}")]
+  NotFound { code: i64, message: String },
+  /// Any other client error, sending the same request again fails the same way.
+  #[error("Definitions request rejected ({code}): {message}")]
+  Rejected { code: i64, message: String },
+  #[error("Definitions server error ({code}): {message}")]
+  Server { code: i64, message: String },
+  #[error("Invalid haptic definitions response at `{path}`: {message}")]
+  Decode { path: String, message: String },
+  /// Without the URL of the request, its query holds the API key.
+  #[error("Failed to reach the definitions server: {0}")]
+  Transport(#[source] reqwest::Error),
+}
+
+impl From<reqwest::Error> for HapticDefinitionsError {
+  fn from(err: reqwest::Error) -> Self {
+    Self::Transport(err.without_url())
+  }
+}
+
+impl HapticDefinitionsError {
+  /// Error of a response with the `code`, either the HTTP status or the `code` of the body.
+  fn from_code(code: i64, message: String) -> Self {
+    match code {
+      401 | 403 => Self::InvalidCredentials { code, message },
+      404 => Self::NotFound { code, message },
+      429 => Self::Server { code, message },
+      400..=499 => Self::Rejected { code, message },
+      _ => Self::Server { code, message },
+    }
+  }
+
+  /// Whether retrying the same request may succeed.
+  pub fn is_transient(&self) -> bool {
+    matches!(self, Self::Server { .. } | Self::Transport(_))
+  }
+}
+
+/// Fetches the deployed workspaces of the applications.
+///
+/// With a `cache_dir` every fetched workspace is stored in `<cache_dir>/<app id>/<version>.json`,
+/// the newest cached version is only refetched if the server has a newer one, and it is used as
+/// is whenever the server cannot be reached or answers with anything but a rejection.
+#[derive(Derivative, Getters, WithSetters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[getset(get = "pub", set_with = "pub")]
+pub struct HapticDefinitionsClient {
+  base_url: String,
+  cache_dir: Option<PathBuf>,
+
+  /// Timeout of a whole request, including reading the response.
+  #[getset(skip)]
+  timeout: Duration,
+  #[getset(skip)]
+  connect_timeout: Duration,
+  /// Number of the retries of the requests failing with a transient error.
+  retries: u32,
+  /// Delay before the first retry, doubled for every next one.
+  retry_backoff: Duration,
+
+  /// Built on the first request, so its connections are reused by the next ones and the clones.
+  #[getset(skip)]
+  #[derivative(Debug = "ignore", PartialEq = "ignore")]
+  http: OnceLock<reqwest::Client>,
+}
+
+impl Default for HapticDefinitionsClient {
+  fn default() -> Self {
+    Self::new(DEFAULT_DEFINITIONS_BASE_URL.to_string())
+  }
+}
+
+impl HapticDefinitionsClient {
+  pub fn new(base_url: String) -> Self {
+    Self {
+      base_url,
+      cache_dir: None,
+      timeout: Duration::from_secs(30),
+      connect_timeout: Duration::from_secs(10),
+      retries: 2,
+      retry_backoff: Duration::from_millis(500),
+      http: OnceLock::new(),
+    }
+  }
+
+  pub fn timeout(&self) -> &Duration {
+    &self.timeout
+  }
+
+  pub fn with_timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = timeout;
+    self.http = OnceLock::new();
+    self
+  }
+
+  pub fn connect_timeout(&self) -> &Duration {
+    &self.connect_timeout
+  }
+
+  pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+    self.connect_timeout = connect_timeout;
+    self.http = OnceLock::new();
+    self
+  }
+
+  fn http(&self) -> Result<&reqwest::Client, HapticDefinitionsError> {
+    if let Some(http) = self.http.get() {
+      return Ok(http);
+    }
+
+    let http = reqwest::Client::builder()
+      .timeout(self.timeout)
+      .connect_timeout(self.connect_timeout)
+      .build()?;
+    Ok(self.http.get_or_init(|| http))
+  }
+
+  pub async fn fetch(
+    &self,
+    app_id: &str,
+    api_key: &str,
+  ) -> Result<HapticDefinitionsMessage, HapticDefinitionsError> {
+    let cached = self.cached(app_id).unwrap_or_else(|err| {
+      warn!("Ignoring the cached haptic definitions of {app_id}: {err:#}");
+      None
+    });
+    let latest_version = cached
+      .as_ref()
+      .and_then(|definitions| *definitions.version())
+      .unwrap_or(-1);
+
+    let result = self
+      .fetch_with_retries(app_id, api_key, latest_version)
+      .await;
+    match (result, cached) {
+      (Ok(Some(definitions)), _) => {
+        if let Err(err) = self.store(app_id, &definitions) {
+          warn!("Failed to cache haptic definitions of {app_id}: {err:#}");
+        }
+        Ok(definitions)
+      }
+      (Ok(None), Some(cached)) => {
+        debug!("Cached haptic definitions of {app_id} are up to date, version {latest_version}");
+        Ok(cached)
+      }
+      (Ok(None), None) => Err(HapticDefinitionsError::NotFound {
+        code: 404,
+        message: "No message in haptic definitions response".to_string(),
+      }),
+      // a rejection is not masked by the cache, the credentials or the app could be revoked
+      (Err(err @ HapticDefinitionsError::InvalidCredentials { .. }), _)
+      | (Err(err @ HapticDefinitionsError::NotFound { .. }), _)
+      | (Err(err @ HapticDefinitionsError::Rejected { .. }), _)
+      | (Err(err), None) => Err(err),
+      (Err(err), Some(cached)) => {
+        warn!("Using cached haptic definitions of {app_id}, version {latest_version}: {err}");
+        Ok(cached)
+      }
+    }
+  }
+
+  async fn fetch_with_retries(
+    &self,
+    app_id: &str,
+    api_key: &str,
+    latest_version: i64,
+  ) -> Result<Option<HapticDefinitionsMessage>, HapticDefinitionsError> {
+    let mut backoff = self.retry_backoff;
+    let mut retries = 0;
+    loop {
+      match self.fetch_newer(app_id, api_key, latest_version).await {
+        Err(err) if err.is_transient() && retries < self.retries => {
+          retries += 1;
+          warn!("Retrying to fetch haptic definitions of {app_id} in {backoff:?}: {err}");
+          tokio::time::sleep(backoff).await;
+          backoff *= 2;
+        }
+        result => return result,
+      }
+    }
+  }
+
+  /// Definitions newer than `latest_version`, `None` if the server has no newer ones.
+  async fn fetch_newer(
+    &self,
+    app_id: &str,
+    api_key: &str,
+    latest_version: i64,
+  ) -> Result<Option<HapticDefinitionsMessage>, HapticDefinitionsError> {
+    let url = format!(
+      "{}/api/v1/haptic-definitions/workspace-v3/latest",
+      self.base_url.trim_end_matches('/')
+    );
+
+    info!("Fetching haptic definitions of {app_id} from {url}, latest version {latest_version}");
+
+    let response = self
+      .http()?
+      .get(url)
+      .query(&[
+        ("latest-version", latest_version.to_string().as_str()),
+        ("api-key", api_key),
+        ("app-id", app_id),
+      ])
+      .send()
+      .await?;
+    let status = response.status();
+    let body = response.text().await?;
+
+    if !status.is_success() {
+      // the body of a failure is not always the usual response
+      let message = serde_json::from_str::<SdkApiResponseV3<serde_json::Value>>(&body)
+        .ok()
+        .and_then(|response| response.error_message)
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or_default().to_string());
+      return Err(HapticDefinitionsError::from_code(
+        i64::from(status.as_u16()),
+        message,
+      ));
+    }
+
+    let deserializer = &mut serde_json::Deserializer::from_str(&body);
+    let response_body: SdkApiResponseV3<HapticDefinitionsMessage> =
+      serde_path_to_error::deserialize(deserializer).map_err(|err| {
+        HapticDefinitionsError::Decode {
+          path: err.path().to_string(),
+          message: err.inner().to_string(),
+        }
+      })?;
+
+    if !response_body.status {
+      return Err(HapticDefinitionsError::from_code(
+        response_body.code,
+        response_body.error_message.unwrap_or_default(),
+      ));
+    }
+
+    let Some(definitions) = response_body.message else {
+      return Ok(None);
+    };
+
+    let newer = definitions
+      .version()
+      .is_none_or(|version| version > latest_version);
+    Ok(newer.then_some(definitions))
+  }
+
+  fn cache(&self) -> Option<DefinitionsCache> {
+    self.cache_dir.clone().map(DefinitionsCache::new)
+  }
+
+  /// Newest cached definitions of the app, see [DefinitionsCache::newest].
+  pub fn cached(&self, app_id: &str) -> anyhow::Result<Option<HapticDefinitionsMessage>> {
+    match self.cache() {
+      Some(cache) => cache.newest(app_id),
+      None => Ok(None),
+    }
+  }
+
+  fn store(&self, app_id: &str, definitions: &HapticDefinitionsMessage) -> anyhow::Result<()> {
+    match self.cache() {
+      Some(cache) => cache.store(app_id, definitions),
+      None => Ok(()),
+    }
+  }
+}
+
+/// Workspaces fetched before, stored in `<dir>/<app id>/<version>.json`.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+pub struct DefinitionsCache {
+  dir: PathBuf,
+}
+
+impl DefinitionsCache {
+  pub fn new(dir: PathBuf) -> Self {
+    Self { dir }
+  }
+
+  fn app_dir(&self, app_id: &str) -> anyhow::Result<PathBuf> {
+    anyhow::ensure!(
+      is_valid_path_segment(app_id),
+      "Invalid app id for the cache: {app_id:?}"
+    );
+
+    Ok(self.dir.join(app_id))
+  }
+
+  /// Cached definitions of the app with the highest version.
+  pub fn newest(&self, app_id: &str) -> anyhow::Result<Option<HapticDefinitionsMessage>> {
+    let dir = self.app_dir(app_id)?;
+    if !dir.exists() {
+      return Ok(None);
+    }
+
+    let mut newest = None;
+    for entry in fs::read_dir(&dir)? {
+      let path = entry?.path();
+      if path.extension().is_none_or(|extension| extension != "json") {
+        continue;
+      }
+      let Some(version) = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.parse::<i64>().ok())
+      else {
+        continue;
+      };
+
+      if newest.as_ref().is_none_or(|(newest, _)| version > *newest) {
+        newest = Some((version, path));
+      }
+    }
+
+    newest.map(|(_, path)| read_cached(&path)).transpose()
+  }
+
+  /// Definitions without a version are not cached, they could not be told apart.
+  pub fn store(&self, app_id: &str, definitions: &HapticDefinitionsMessage) -> anyhow::Result<()> {
+    let Some(version) = definitions.version() else {
+      return Ok(());
+    };
+
+    let dir = self.app_dir(app_id)?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{version}.json"));
+    let partial = dir.join(format!("{version}.json.partial"));
+    fs::write(&partial, serde_json::to_vec(definitions)?)?;
+    fs::rename(&partial, &path)?;
+
+    debug!(
+      "Cached haptic definitions of {app_id} in {}",
+      path.display()
+    );
+    Ok(())
+  }
+}
+
+fn read_cached(path: &Path) -> anyhow::Result<HapticDefinitionsMessage> {
+  let json = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+  serde_json::from_slice(&json).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+pub async fn fetch_haptic_definitions(
+  app_id: &str,
+  api_key: &str,
+) -> Result<HapticDefinitionsMessage, HapticDefinitionsError> {
+  HapticDefinitionsClient::default()
+    .fetch(app_id, api_key)
+    .await
+}
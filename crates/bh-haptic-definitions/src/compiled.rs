@@ -0,0 +1,496 @@
+//! Compact binary form of rendered patterns, which can be played without parsing and rendering
+//! the haptic definitions again.
+//!
+//! All integers are little endian:
+//!
+//! ```text
+//! header    magic "BHCP", version: u16, reserved: u16, tick_millis: u32, pattern_count: u32
+//! index     pattern_count x (key_offset: u32, key_len: u32, pattern_offset: u32, pattern_len: u32),
+//!           sorted by the key
+//! pattern   duration_millis: u32, frame_count: u32, position_count: u32, then for every position
+//!           name_len: u16, motor_count: u16, name, frame_count x motor_count intensities: u8
+//! ```
+//!
+//! Offsets are from the start of the data. Intensities are quantized to `0..=255`.
+
+use anyhow::{Context, Result, bail, ensure};
+use std::collections::HashMap;
+
+use crate::{DevicePosition, HapticDefinitionsMessage, HapticTimeline, RenderOptions};
+
+pub const COMPILED_PATTERNS_MAGIC: &[u8; 4] = b"BHCP";
+pub const COMPILED_PATTERNS_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = 16;
+const INDEX_ENTRY_LEN: usize = 16;
+
+/// Patterns in the compiled form, read straight from the underlying bytes, e.g. a memory mapped
+/// file. The structure is checked once when loading, patterns are decoded on demand.
+#[derive(Debug, Clone)]
+pub struct CompiledPatterns<B = Vec<u8>> {
+  data: B,
+  tick_millis: u32,
+  pattern_count: usize,
+}
+
+/// Single pattern of [CompiledPatterns].
+#[derive(Debug, Clone, Copy)]
+pub struct CompiledPattern<'a> {
+  tick_millis: u32,
+  duration_millis: u32,
+  frame_count: usize,
+  position_count: usize,
+  positions: &'a [u8],
+}
+
+/// Frames of a single position of a [CompiledPattern].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledPosition<'a> {
+  position: DevicePosition,
+  motor_count: usize,
+  frames: &'a [u8],
+}
+
+impl HapticDefinitionsMessage {
+  /// Renders every mapping and compiles them under their keys.
+  pub fn compile(&self, options: &RenderOptions) -> Result<CompiledPatterns> {
+    let timelines = self
+      .haptic_mappings()
+      .iter()
+      .map(|mapping| (mapping.key().as_str(), mapping.render(options)))
+      .collect::<Vec<_>>();
+
+    CompiledPatterns::compile(
+      *options.tick_millis(),
+      timelines.iter().map(|(key, timeline)| (*key, timeline)),
+    )
+  }
+}
+
+impl CompiledPatterns {
+  /// Compiles the timelines, which are expected to be sampled every `tick_millis`. The last
+  /// timeline wins for a duplicate key.
+  ///
+  /// Fails if the patterns do not fit the format, e.g. a position with more than `u16::MAX`
+  /// motors or more than 4 GiB of data.
+  pub fn compile<'a>(
+    tick_millis: u32,
+    timelines: impl IntoIterator<Item = (&'a str, &'a HapticTimeline)>,
+  ) -> Result<Self> {
+    let timelines = timelines.into_iter().collect::<HashMap<_, _>>();
+    let mut timelines = timelines.into_iter().collect::<Vec<_>>();
+    timelines.sort_by_key(|(key, _)| *key);
+
+    let pattern_count = timelines.len();
+    let body_offset = HEADER_LEN + pattern_count * INDEX_ENTRY_LEN;
+    let mut index = Vec::with_capacity(pattern_count * INDEX_ENTRY_LEN);
+    let mut body = Vec::new();
+
+    for (key, timeline) in timelines {
+      let key_offset = body_offset + body.len();
+      body.extend_from_slice(key.as_bytes());
+
+      let pattern_offset = body_offset + body.len();
+      write_pattern(timeline, &mut body).with_context(|| format!("Failed to compile {key}"))?;
+
+      for value in [
+        key_offset,
+        key.len(),
+        pattern_offset,
+        body_offset + body.len() - pattern_offset,
+      ] {
+        write_u32(&mut index, value, "offset")?;
+      }
+    }
+
+    let mut data = Vec::with_capacity(body_offset + body.len());
+    data.extend_from_slice(COMPILED_PATTERNS_MAGIC);
+    data.extend_from_slice(&COMPILED_PATTERNS_VERSION.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&tick_millis.to_le_bytes());
+    write_u32(&mut data, pattern_count, "pattern count")?;
+    data.extend_from_slice(&index);
+    data.extend_from_slice(&body);
+
+    Ok(Self {
+      data,
+      tick_millis,
+      pattern_count,
+    })
+  }
+}
+
+#[cfg(feature = "mmap")]
+impl CompiledPatterns<memmap2::Mmap> {
+  /// Maps the compiled file into memory.
+  ///
+  /// # Safety
+  ///
+  /// The file must not be modified or truncated, by this or any other process, while the
+  /// patterns are alive, see [memmap2::Mmap::map]. Reading a modified mapping is undefined
+  /// behaviour and reading a truncated one can crash the process. Use
+  /// [CompiledPatterns::from_bytes] with the file read into memory otherwise.
+  pub unsafe fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)
+      .with_context(|| format!("Failed to open compiled patterns {}", path.display()))?;
+
+    // SAFETY: the caller keeps the file unchanged while mapped
+    let data = unsafe { memmap2::Mmap::map(&file) }
+      .with_context(|| format!("Failed to map compiled patterns {}", path.display()))?;
+
+    Self::from_bytes(data)
+  }
+}
+
+impl<B: AsRef<[u8]>> CompiledPatterns<B> {
+  /// Checks the header, the index and the structure of every pattern.
+  pub fn from_bytes(data: B) -> Result<Self> {
+    let mut reader = Reader::new(data.as_ref());
+
+    let magic = reader.bytes(4)?;
+    ensure!(
+      magic == COMPILED_PATTERNS_MAGIC,
+      "Not compiled patterns, magic is {magic:?}"
+    );
+    let version = reader.u16()?;
+    ensure!(
+      version == COMPILED_PATTERNS_VERSION,
+      "Unsupported compiled patterns version {version}, expected {COMPILED_PATTERNS_VERSION}"
+    );
+    reader.u16()?;
+    let tick_millis = reader.u32()?;
+    let pattern_count = reader.u32()? as usize;
+
+    let compiled = Self {
+      tick_millis,
+      pattern_count,
+      data,
+    };
+
+    let mut previous_key = None;
+    for i in 0..pattern_count {
+      let (key, pattern) = compiled
+        .entry(i)
+        .with_context(|| format!("Invalid compiled pattern {i}"))?;
+      if previous_key.is_some_and(|previous| previous >= key) {
+        bail!("Compiled pattern {key} is out of order");
+      }
+      previous_key = Some(key);
+
+      CompiledPattern::read(tick_millis, pattern)
+        .with_context(|| format!("Invalid compiled pattern {key}"))?
+        .check()
+        .with_context(|| format!("Invalid compiled pattern {key}"))?;
+    }
+
+    Ok(compiled)
+  }
+
+  pub fn as_bytes(&self) -> &[u8] {
+    self.data.as_ref()
+  }
+
+  pub fn tick_millis(&self) -> u32 {
+    self.tick_millis
+  }
+
+  pub fn len(&self) -> usize {
+    self.pattern_count
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.pattern_count == 0
+  }
+
+  /// Keys of the patterns, sorted.
+  pub fn keys(&self) -> impl Iterator<Item = &str> {
+    (0..self.pattern_count).filter_map(|i| Some(self.entry(i).ok()?.0))
+  }
+
+  pub fn pattern(&self, key: &str) -> Option<CompiledPattern<'_>> {
+    let mut range = 0..self.pattern_count;
+
+    while !range.is_empty() {
+      let middle = range.start + range.len() / 2;
+      let (middle_key, pattern) = self.entry(middle).ok()?;
+      match middle_key.cmp(key) {
+        std::cmp::Ordering::Less => range.start = middle + 1,
+        std::cmp::Ordering::Greater => range.end = middle,
+        std::cmp::Ordering::Equal => return CompiledPattern::read(self.tick_millis, pattern).ok(),
+      }
+    }
+
+    None
+  }
+
+  /// Key and pattern bytes of the `i`-th index entry.
+  fn entry(&self, i: usize) -> Result<(&str, &[u8])> {
+    let data = self.data.as_ref();
+    let mut reader = Reader::new(data);
+    reader.skip(HEADER_LEN + i * INDEX_ENTRY_LEN)?;
+
+    let (key_offset, key_len) = (reader.u32()? as usize, reader.u32()? as usize);
+    let (pattern_offset, pattern_len) = (reader.u32()? as usize, reader.u32()? as usize);
+
+    let key = data
+      .get(key_offset..key_offset + key_len)
+      .context("Compiled pattern key is out of bounds")?;
+    let pattern = data
+      .get(pattern_offset..pattern_offset + pattern_len)
+      .context("Compiled pattern is out of bounds")?;
+
+    Ok((std::str::from_utf8(key)?, pattern))
+  }
+}
+
+impl<'a> CompiledPattern<'a> {
+  fn read(tick_millis: u32, data: &'a [u8]) -> Result<Self> {
+    let mut reader = Reader::new(data);
+
+    Ok(Self {
+      tick_millis,
+      duration_millis: reader.u32()?,
+      frame_count: reader.u32()? as usize,
+      position_count: reader.u32()? as usize,
+      positions: reader.rest(),
+    })
+  }
+
+  fn check(&self) -> Result<()> {
+    let mut reader = Reader::new(self.positions);
+    for _ in 0..self.position_count {
+      read_position(&mut reader, self.frame_count)?;
+    }
+    ensure!(reader.rest().is_empty(), "Unexpected data after positions");
+
+    Ok(())
+  }
+
+  pub fn tick_millis(&self) -> u32 {
+    self.tick_millis
+  }
+
+  pub fn duration_millis(&self) -> u32 {
+    self.duration_millis
+  }
+
+  pub fn frame_count(&self) -> usize {
+    self.frame_count
+  }
+
+  pub fn positions(&self) -> impl Iterator<Item = CompiledPosition<'a>> + use<'a> {
+    let mut reader = Reader::new(self.positions);
+    let frame_count = self.frame_count;
+
+    (0..self.position_count).map_while(move |_| read_position(&mut reader, frame_count).ok())
+  }
+
+  pub fn position(&self, position: &DevicePosition) -> Option<CompiledPosition<'a>> {
+    self
+      .positions()
+      .find(|compiled| compiled.position == *position)
+  }
+
+  /// Decodes the pattern back into a [HapticTimeline].
+  pub fn to_timeline(&self) -> HapticTimeline {
+    let mut timeline = HapticTimeline::new(self.tick_millis, self.duration_millis);
+
+    for compiled in self.positions() {
+      let target = timeline.position_mut(compiled.position.clone(), compiled.motor_count);
+      for tick in 0..self.frame_count {
+        for motor in 0..compiled.motor_count {
+          target.set_intensity(tick, motor, compiled.intensity(tick, motor));
+        }
+      }
+    }
+
+    timeline
+  }
+}
+
+impl<'a> CompiledPosition<'a> {
+  pub fn position(&self) -> &DevicePosition {
+    &self.position
+  }
+
+  pub fn motor_count(&self) -> usize {
+    self.motor_count
+  }
+
+  /// Quantized intensities of the motors at the tick.
+  pub fn frame(&self, tick: usize) -> Option<&'a [u8]> {
+    let start = tick * self.motor_count;
+    self.frames.get(start..start + self.motor_count)
+  }
+
+  pub fn intensity(&self, tick: usize, motor: usize) -> f64 {
+    self
+      .frame(tick)
+      .and_then(|frame| frame.get(motor))
+      .map(|intensity| f64::from(*intensity) / f64::from(u8::MAX))
+      .unwrap_or(0.0)
+  }
+}
+
+fn write_pattern(timeline: &HapticTimeline, data: &mut Vec<u8>) -> Result<()> {
+  let frame_count = timeline.frame_count();
+  let mut positions = timeline.positions().iter().collect::<Vec<_>>();
+  positions.sort_by_key(|(position, _)| position.to_string());
+
+  data.extend_from_slice(&timeline.duration_millis().to_le_bytes());
+  write_u32(data, frame_count, "frame count")?;
+  write_u32(data, positions.len(), "position count")?;
+
+  for (position, position_timeline) in positions {
+    let name = position.to_string();
+    let motor_count = *position_timeline.motor_count();
+
+    write_u16(data, name.len(), "position name length")?;
+    write_u16(data, motor_count, "motor count")?;
+    data.extend_from_slice(name.as_bytes());
+    for tick in 0..frame_count {
+      for motor in 0..motor_count {
+        let intensity = position_timeline.intensity(tick, motor).clamp(0.0, 1.0);
+        data.push((intensity * f64::from(u8::MAX)).round() as u8);
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn write_u16(data: &mut Vec<u8>, value: usize, what: &str) -> Result<()> {
+  let value = u16::try_from(value).with_context(|| format!("Too large {what}: {value}"))?;
+  data.extend_from_slice(&value.to_le_bytes());
+  Ok(())
+}
+
+fn write_u32(data: &mut Vec<u8>, value: usize, what: &str) -> Result<()> {
+  let value = u32::try_from(value).with_context(|| format!("Too large {what}: {value}"))?;
+  data.extend_from_slice(&value.to_le_bytes());
+  Ok(())
+}
+
+fn read_position<'a>(reader: &mut Reader<'a>, frame_count: usize) -> Result<CompiledPosition<'a>> {
+  let name_len = reader.u16()? as usize;
+  let motor_count = reader.u16()? as usize;
+  let name = std::str::from_utf8(reader.bytes(name_len)?)?;
+  let frames = reader.bytes(frame_count * motor_count)?;
+
+  Ok(CompiledPosition {
+    position: name
+      .parse()
+      .unwrap_or(DevicePosition::Unknown(name.to_string())),
+    motor_count,
+    frames,
+  })
+}
+
+struct Reader<'a> {
+  data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  fn new(data: &'a [u8]) -> Self {
+    Self { data }
+  }
+
+  fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+    ensure!(len <= self.data.len(), "Compiled patterns are truncated");
+    let (bytes, rest) = self.data.split_at(len);
+    self.data = rest;
+    Ok(bytes)
+  }
+
+  fn skip(&mut self, len: usize) -> Result<()> {
+    self.bytes(len).map(|_| ())
+  }
+
+  fn u16(&mut self) -> Result<u16> {
+    Ok(u16::from_le_bytes(self.bytes(2)?.try_into()?))
+  }
+
+  fn u32(&mut self) -> Result<u32> {
+    Ok(u32::from_le_bytes(self.bytes(4)?.try_into()?))
+  }
+
+  fn rest(&self) -> &'a [u8] {
+    self.data
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn timeline() -> HapticTimeline {
+    let mut timeline = HapticTimeline::new(20, 100);
+    let front = timeline.position_mut(DevicePosition::VestFront, 20);
+    front.set_intensity(0, 0, 1.0);
+    front.set_intensity(4, 19, 0.5);
+    timeline
+      .position_mut(DevicePosition::ForearmL, 6)
+      .set_intensity(2, 3, 0.25);
+    timeline
+  }
+
+  #[test]
+  fn compiled_patterns_round_trip() -> Result<()> {
+    let timeline = timeline();
+    let empty = HapticTimeline::new(20, 0);
+    let compiled = CompiledPatterns::compile(20, [("hit", &timeline), ("empty", &empty)])?;
+    let loaded = CompiledPatterns::from_bytes(compiled.as_bytes())?;
+
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded.keys().collect::<Vec<_>>(), vec!["empty", "hit"]);
+    assert!(loaded.pattern("miss").is_none());
+
+    let hit = loaded.pattern("hit").unwrap();
+    assert_eq!(hit.frame_count(), 5);
+    let front = hit.position(&DevicePosition::VestFront).unwrap();
+    assert_eq!(front.frame(0).unwrap()[0], 255);
+    assert_eq!(front.frame(4).unwrap()[19], 128);
+
+    let decoded = hit.to_timeline();
+    for (position, expected) in timeline.positions() {
+      let actual = decoded.position(position).unwrap();
+      for (tick, frame) in expected.frames().iter().enumerate() {
+        for (motor, intensity) in frame.iter().enumerate() {
+          assert!((actual.intensity(tick, motor) - intensity).abs() <= 0.5 / 255.0);
+        }
+      }
+    }
+    assert_eq!(loaded.pattern("empty").unwrap().to_timeline(), empty);
+
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_data_is_rejected() {
+    let compiled = CompiledPatterns::compile(20, [("hit", &timeline())]).unwrap();
+    let bytes = compiled.as_bytes();
+
+    assert!(CompiledPatterns::from_bytes(&b"JSON"[..]).is_err());
+
+    let mut future = bytes.to_vec();
+    future[4] = 2;
+    assert!(CompiledPatterns::from_bytes(future).is_err());
+
+    for len in [10, HEADER_LEN + 4, bytes.len() - 1] {
+      assert!(
+        CompiledPatterns::from_bytes(&bytes[..len]).is_err(),
+        "{len}"
+      );
+    }
+  }
+
+  #[test]
+  fn patterns_not_fitting_the_format_are_rejected() {
+    let mut timeline = HapticTimeline::new(20, 20);
+    timeline.position_mut(DevicePosition::VestFront, usize::from(u16::MAX) + 1);
+
+    let err = CompiledPatterns::compile(20, [("wide", &timeline)]).unwrap_err();
+    assert!(format!("{err:#}").contains("motor count"), "{err:#}");
+  }
+}
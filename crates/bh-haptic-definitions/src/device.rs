@@ -1,9 +1,12 @@
 use derivative::Derivative;
 use strum::{Display as StrumDisplay, EnumString};
 
+/// Position keys as used by the `.tact` files (`modes`, `layouts`) and the SDK messages.
+///
+/// Keys, which are not known (yet), are kept in [DevicePosition::Unknown] and written back as
+/// they were.
 #[derive(Derivative, StrumDisplay, EnumString)]
 #[derivative(Debug, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DevicePosition {
   Head,
   Tactal,
@@ -23,9 +26,16 @@ pub enum DevicePosition {
 
   FootL,
   FootR,
+
+  #[strum(default, to_string = "{0}")]
+  Unknown(String),
 }
 
 impl DevicePosition {
+  pub fn is_unknown(&self) -> bool {
+    matches!(self, DevicePosition::Unknown(_))
+  }
+
   pub fn is_right(&self) -> bool {
     matches!(
       self,
@@ -45,6 +55,42 @@ impl DevicePosition {
         | DevicePosition::FootL
     )
   }
+
+  /// The same position on the other side of the body, midline positions are left as is.
+  pub fn mirrored(&self) -> DevicePosition {
+    match self {
+      DevicePosition::GloveL => DevicePosition::GloveR,
+      DevicePosition::GloveR => DevicePosition::GloveL,
+      DevicePosition::HandL => DevicePosition::HandR,
+      DevicePosition::HandR => DevicePosition::HandL,
+      DevicePosition::ForearmL => DevicePosition::ForearmR,
+      DevicePosition::ForearmR => DevicePosition::ForearmL,
+      DevicePosition::FootL => DevicePosition::FootR,
+      DevicePosition::FootR => DevicePosition::FootL,
+      other => other.clone(),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DevicePosition {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DevicePosition {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    let key = String::deserialize(deserializer)?;
+    Ok(key.parse().unwrap_or(DevicePosition::Unknown(key)))
+  }
 }
 
 #[derive(Derivative, StrumDisplay, EnumString)]
@@ -75,3 +121,205 @@ pub enum DeviceType {
   Hand,
   Foot,
 }
+
+/// Codes of the positions in the `ServerDevices` message, same as the `PositionType` of the
+/// official SDK.
+const POSITION_CODES: [(u32, DevicePosition); 10] = [
+  (0, DevicePosition::Vest),
+  (1, DevicePosition::ForearmL),
+  (2, DevicePosition::ForearmR),
+  (3, DevicePosition::Head),
+  (4, DevicePosition::HandL),
+  (5, DevicePosition::HandR),
+  (6, DevicePosition::FootL),
+  (7, DevicePosition::FootR),
+  (8, DevicePosition::GloveL),
+  (9, DevicePosition::GloveR),
+];
+
+impl DevicePosition {
+  /// Numeric code of the position in the `ServerDevices` message.
+  pub fn code(&self) -> Option<u32> {
+    let position = match self {
+      DevicePosition::VestFront | DevicePosition::VestBack => &DevicePosition::Vest,
+      DevicePosition::Tactal => &DevicePosition::Head,
+      other => other,
+    };
+
+    POSITION_CODES
+      .iter()
+      .find(|(_, known)| known == position)
+      .map(|(code, _)| *code)
+  }
+
+  pub fn from_code(code: u32) -> Option<DevicePosition> {
+    POSITION_CODES
+      .iter()
+      .find(|(known, _)| *known == code)
+      .map(|(_, position)| position.clone())
+  }
+}
+
+impl DeviceType {
+  /// Parses the name a device advertises, e.g. `TactSuitX40` or `Tactosy2_V3 (L)`, into its type
+  /// and the position it is worn at.
+  pub fn from_device_name(name: &str) -> Option<(DeviceType, DevicePosition)> {
+    let name = name.trim();
+    let (name, side) = match name.rsplit_once(' ') {
+      Some((name, "(L)")) => (name, Some(Side::Left)),
+      Some((name, "(R)")) => (name, Some(Side::Right)),
+      _ => (name, None),
+    };
+    // firmware revision, e.g. `_V3`
+    let name = match name.rsplit_once("_V") {
+      Some((name, version)) if version.chars().all(|c| c.is_ascii_digit()) => name,
+      _ => name,
+    };
+
+    let device_type = name.parse::<DeviceType>().ok().or_else(|| {
+      // gloves have a type per side
+      let suffix = match side? {
+        Side::Left => "L",
+        Side::Right => "R",
+      };
+      format!("{name}{suffix}").parse().ok()
+    })?;
+    let position = device_type.worn_at(side)?;
+
+    Some((device_type, position))
+  }
+
+  /// Name of the device as [DeviceType::from_device_name] parses it, the side is appended for
+  /// devices worn in pairs.
+  pub fn device_name(&self, position: &DevicePosition) -> String {
+    let name = match self {
+      DeviceType::TactGloveL | DeviceType::TactGloveR => "TactGlove".to_string(),
+      other => other.to_string(),
+    };
+    let side = match Side::of(position) {
+      Some(Side::Left) if self.is_paired() => " (L)",
+      Some(Side::Right) if self.is_paired() => " (R)",
+      _ => "",
+    };
+
+    name + side
+  }
+
+  /// Devices worn on both sides of the body have a single type for both of them.
+  fn is_paired(&self) -> bool {
+    self
+      .positions()
+      .iter()
+      .any(|position| position.is_left() || position.is_right())
+  }
+
+  /// Position the whole device is addressed at, `None` if the side of a paired device is missing.
+  fn worn_at(&self, side: Option<Side>) -> Option<DevicePosition> {
+    let positions = self.positions();
+    if positions.contains(&DevicePosition::VestFront) {
+      return Some(DevicePosition::Vest);
+    }
+
+    match (positions.as_slice(), side) {
+      ([position], _) => Some(position.clone()),
+      (positions, Some(side)) => positions
+        .iter()
+        .find(|position| Side::of(position) == Some(side))
+        .cloned(),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+  Left,
+  Right,
+}
+
+impl Side {
+  fn of(position: &DevicePosition) -> Option<Side> {
+    if position.is_left() {
+      Some(Side::Left)
+    } else if position.is_right() {
+      Some(Side::Right)
+    } else {
+      None
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unknown_positions_keep_their_key() {
+    assert_eq!(
+      "ForearmL".parse::<DevicePosition>(),
+      Ok(DevicePosition::ForearmL)
+    );
+    assert_eq!(
+      "Tail".parse::<DevicePosition>(),
+      Ok(DevicePosition::Unknown("Tail".to_string()))
+    );
+    assert_eq!(
+      DevicePosition::Unknown("Tail".to_string()).to_string(),
+      "Tail"
+    );
+    assert_eq!(DevicePosition::VestFront.to_string(), "VestFront");
+  }
+
+  #[test]
+  fn position_codes() {
+    for code in 0..10 {
+      let position = DevicePosition::from_code(code).unwrap();
+      assert_eq!(position.code(), Some(code));
+    }
+
+    assert_eq!(DevicePosition::from_code(10), None);
+    assert_eq!(DevicePosition::VestBack.code(), Some(0));
+    assert_eq!(DevicePosition::Unknown("Tail".to_string()).code(), None);
+  }
+
+  #[test]
+  fn device_names() {
+    assert_eq!(
+      DeviceType::from_device_name("TactSuitX40"),
+      Some((DeviceType::TactSuitX40, DevicePosition::Vest))
+    );
+    assert_eq!(
+      DeviceType::from_device_name("Tactosy2_V3 (L)"),
+      Some((DeviceType::Tactosy2, DevicePosition::ForearmL))
+    );
+    assert_eq!(
+      DeviceType::from_device_name("TactosyH_V2 (R)"),
+      Some((DeviceType::TactosyH, DevicePosition::HandR))
+    );
+    assert_eq!(
+      DeviceType::from_device_name("TactGlove (R)"),
+      Some((DeviceType::TactGloveR, DevicePosition::GloveR))
+    );
+    assert_eq!(
+      DeviceType::from_device_name("Tactal_V2"),
+      Some((DeviceType::Tactal, DevicePosition::Head))
+    );
+    // the side of a paired device is required
+    assert_eq!(DeviceType::from_device_name("Tactosy2_V3"), None);
+    assert_eq!(DeviceType::from_device_name("Toaster"), None);
+
+    for (device_type, position) in [
+      (DeviceType::TactSuitX16, DevicePosition::Vest),
+      (DeviceType::Tactosy2, DevicePosition::ForearmR),
+      (DeviceType::TactGloveL, DevicePosition::GloveL),
+      (DeviceType::TactVisor, DevicePosition::Head),
+    ] {
+      let name = device_type.device_name(&position);
+      assert_eq!(
+        DeviceType::from_device_name(&name),
+        Some((device_type, position)),
+        "{name}"
+      );
+    }
+  }
+}
@@ -0,0 +1,317 @@
+use derivative::Derivative;
+use derive_more::with_trait::Display;
+use getset::Getters;
+
+use crate::{
+  DevicePosition, HapticDefinitionMapping, HapticDefinitionTactFilePattern,
+  HapticDefinitionsMessage, HapticDefinitionsRegistry,
+};
+
+/// Change of a mapping found by [HapticDefinitionMapping::diff].
+#[derive(Derivative, Display)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "camelCase"))]
+pub enum MappingChange {
+  #[display("enable {from} -> {to}")]
+  Enable { from: bool, to: bool },
+
+  #[display("intensity {from:?} -> {to:?}")]
+  Intensity { from: Option<f64>, to: Option<f64> },
+
+  #[display("event time {from} -> {to}")]
+  EventTime { from: u32, to: u32 },
+
+  #[display("category {from:?} -> {to:?}")]
+  Category {
+    from: Option<String>,
+    to: Option<String>,
+  },
+
+  #[display("plays on {position}")]
+  PositionAdded { position: DevicePosition },
+
+  #[display("no longer plays on {position}")]
+  PositionRemoved { position: DevicePosition },
+
+  #[display("added {position} pattern")]
+  PatternAdded { position: DevicePosition },
+
+  #[display("removed {position} pattern")]
+  PatternRemoved { position: DevicePosition },
+
+  /// The tracks are the same, but not the layout or the rest of the project.
+  #[display("modified {position} pattern")]
+  PatternModified { position: DevicePosition },
+
+  #[display("added track {index} of {position} pattern")]
+  TrackAdded {
+    position: DevicePosition,
+    index: usize,
+  },
+
+  #[display("removed track {index} of {position} pattern")]
+  TrackRemoved {
+    position: DevicePosition,
+    index: usize,
+  },
+
+  #[display("modified track {index} of {position} pattern")]
+  TrackModified {
+    position: DevicePosition,
+    index: usize,
+  },
+
+  #[display("modified audio patterns")]
+  AudioPatternsModified,
+}
+
+impl MappingChange {
+  /// Whether the mapping plays differently, not just with another intensity or enable flag.
+  pub fn changes_patterns(&self) -> bool {
+    match self {
+      MappingChange::Enable { .. }
+      | MappingChange::Intensity { .. }
+      | MappingChange::Category { .. }
+      | MappingChange::PositionAdded { .. }
+      | MappingChange::PositionRemoved { .. } => false,
+      MappingChange::EventTime { .. }
+      | MappingChange::PatternAdded { .. }
+      | MappingChange::PatternRemoved { .. }
+      | MappingChange::PatternModified { .. }
+      | MappingChange::TrackAdded { .. }
+      | MappingChange::TrackRemoved { .. }
+      | MappingChange::TrackModified { .. }
+      | MappingChange::AudioPatternsModified => true,
+    }
+  }
+}
+
+/// Changes of a mapping present in both versions.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MappingDiff {
+  key: String,
+  changes: Vec<MappingChange>,
+}
+
+impl MappingDiff {
+  pub fn new(key: String, changes: Vec<MappingChange>) -> Self {
+    Self { key, changes }
+  }
+
+  /// See [MappingChange::changes_patterns].
+  pub fn changes_patterns(&self) -> bool {
+    self.changes.iter().any(MappingChange::changes_patterns)
+  }
+}
+
+/// What changed between two versions of the haptic definitions, by the mapping key.
+///
+/// Only the first mapping of a duplicate key is compared, like in [HapticDefinitionsRegistry].
+/// The ids, descriptions and update times of the mappings are not compared.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct HapticDefinitionsDiff {
+  from_version: Option<i64>,
+  to_version: Option<i64>,
+
+  /// Keys only in the newer definitions, in their order.
+  added: Vec<String>,
+  /// Keys only in the older definitions, in their order.
+  removed: Vec<String>,
+  /// Mappings in the order of the newer definitions.
+  modified: Vec<MappingDiff>,
+}
+
+impl HapticDefinitionsDiff {
+  pub fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+  }
+
+  /// Keys of the mappings to compile again, the added ones and the ones which play differently.
+  pub fn recompile_keys(&self) -> impl Iterator<Item = &str> {
+    let modified = self
+      .modified
+      .iter()
+      .filter(|mapping| mapping.changes_patterns())
+      .map(|mapping| mapping.key().as_str());
+
+    self.added.iter().map(String::as_str).chain(modified)
+  }
+}
+
+impl HapticDefinitionsMessage {
+  /// Changes from these definitions to the `newer` ones.
+  pub fn diff(&self, newer: &HapticDefinitionsMessage) -> HapticDefinitionsDiff {
+    let old = HapticDefinitionsRegistry::new(self.clone());
+    let new = HapticDefinitionsRegistry::new(newer.clone());
+
+    let added = new
+      .mappings()
+      .filter(|mapping| !old.contains(mapping.key()))
+      .map(|mapping| mapping.key().clone())
+      .collect();
+    let removed = old
+      .mappings()
+      .filter(|mapping| !new.contains(mapping.key()))
+      .map(|mapping| mapping.key().clone())
+      .collect();
+    let modified = new
+      .mappings()
+      .filter_map(|mapping| {
+        let changes = old.get(mapping.key())?.diff(mapping);
+        (!changes.is_empty()).then(|| MappingDiff::new(mapping.key().clone(), changes))
+      })
+      .collect();
+
+    HapticDefinitionsDiff {
+      from_version: *self.version(),
+      to_version: *newer.version(),
+      added,
+      removed,
+      modified,
+    }
+  }
+}
+
+impl HapticDefinitionMapping {
+  /// Changes from this mapping to the `newer` one, the keys are not compared.
+  pub fn diff(&self, newer: &HapticDefinitionMapping) -> Vec<MappingChange> {
+    let mut changes = vec![];
+
+    if self.is_enabled() != newer.is_enabled() {
+      changes.push(MappingChange::Enable {
+        from: self.is_enabled(),
+        to: newer.is_enabled(),
+      });
+    }
+    if self.intensity() != newer.intensity() {
+      changes.push(MappingChange::Intensity {
+        from: *self.intensity(),
+        to: *newer.intensity(),
+      });
+    }
+    if self.event_time() != newer.event_time() {
+      changes.push(MappingChange::EventTime {
+        from: *self.event_time(),
+        to: *newer.event_time(),
+      });
+    }
+    if self.category() != newer.category() {
+      changes.push(MappingChange::Category {
+        from: self.category().clone(),
+        to: newer.category().clone(),
+      });
+    }
+
+    let old_positions = self.positions();
+    let new_positions = newer.positions();
+    for position in &new_positions {
+      if !old_positions.contains(position) {
+        changes.push(MappingChange::PositionAdded {
+          position: position.clone(),
+        });
+      }
+    }
+    for position in &old_positions {
+      if !new_positions.contains(position) {
+        changes.push(MappingChange::PositionRemoved {
+          position: position.clone(),
+        });
+      }
+    }
+
+    diff_tact_patterns(
+      self.tact_file_patterns(),
+      newer.tact_file_patterns(),
+      &mut changes,
+    );
+
+    if self.audio_file_patterns() != newer.audio_file_patterns() {
+      changes.push(MappingChange::AudioPatternsModified);
+    }
+
+    changes
+  }
+}
+
+/// Compares the patterns by their position, the first pattern of a position wins.
+fn diff_tact_patterns(
+  old: &[HapticDefinitionTactFilePattern],
+  new: &[HapticDefinitionTactFilePattern],
+  changes: &mut Vec<MappingChange>,
+) {
+  let old_patterns = first_by_position(old);
+  let new_patterns = first_by_position(new);
+
+  for (position, pattern) in &new_patterns {
+    let Some((_, old_pattern)) = old_patterns.iter().find(|(old, _)| old == position) else {
+      changes.push(MappingChange::PatternAdded {
+        position: position.clone(),
+      });
+      continue;
+    };
+    if old_pattern == pattern {
+      continue;
+    }
+
+    let old_tracks = old_pattern.tact_file().tracks();
+    let new_tracks = pattern.tact_file().tracks();
+    let change_count = changes.len();
+    for index in 0..old_tracks.len().max(new_tracks.len()) {
+      match (old_tracks.get(index), new_tracks.get(index)) {
+        (Some(old_track), Some(new_track)) if old_track != new_track => {
+          changes.push(MappingChange::TrackModified {
+            position: position.clone(),
+            index,
+          });
+        }
+        (None, Some(_)) => changes.push(MappingChange::TrackAdded {
+          position: position.clone(),
+          index,
+        }),
+        (Some(_), None) => changes.push(MappingChange::TrackRemoved {
+          position: position.clone(),
+          index,
+        }),
+        _ => {}
+      }
+    }
+
+    if changes.len() == change_count {
+      changes.push(MappingChange::PatternModified {
+        position: position.clone(),
+      });
+    }
+  }
+
+  for (position, _) in &old_patterns {
+    if !new_patterns.iter().any(|(new, _)| new == position) {
+      changes.push(MappingChange::PatternRemoved {
+        position: position.clone(),
+      });
+    }
+  }
+}
+
+fn first_by_position(
+  patterns: &[HapticDefinitionTactFilePattern],
+) -> Vec<(DevicePosition, &HapticDefinitionTactFilePattern)> {
+  let mut by_position = Vec::<(DevicePosition, &HapticDefinitionTactFilePattern)>::new();
+  for pattern in patterns {
+    if !by_position
+      .iter()
+      .any(|(position, _)| position == pattern.position())
+    {
+      by_position.push((pattern.position().clone(), pattern));
+    }
+  }
+  by_position
+}
@@ -1,13 +1,36 @@
+mod analysis;
+mod audio;
+mod catalogue;
+#[cfg(feature = "client")]
+mod client;
+mod compiled;
 mod device;
+mod diff;
+#[cfg(feature = "serde")]
+mod local;
+mod registry;
+mod render;
 mod tact;
+mod transform;
+mod validate;
 
+pub use analysis::*;
+pub use audio::*;
+#[cfg(feature = "client")]
+pub use client::*;
+pub use compiled::*;
 pub use device::*;
+pub use diff::*;
+#[cfg(feature = "serde")]
+pub use local::*;
+pub use registry::*;
+pub use render::*;
 pub use tact::*;
+pub use transform::*;
+pub use validate::*;
 
-use anyhow::*;
 use derivative::Derivative;
 use getset::{Getters, WithSetters};
-use tracing::*;
 
 #[derive(Derivative, Getters)]
 #[derivative(Debug, Clone, PartialEq, Eq, Hash)]
@@ -40,30 +63,6 @@ impl<T> SdkApiResponseV3<T> {
   }
 }
 
-#[cfg(feature = "client")]
-pub async fn fetch_haptic_definitions(
-  app_id: &str,
-  api_key: &str,
-  // version: String,
-) -> Result<HapticDefinitionsMessage, Error> {
-  let url = format!(
-    "https://sdk-apis.bhaptics.com/api/v1/haptic-definitions/workspace-v3/latest?latest-version={}&api-key={}&app-id={}",
-    -1, api_key, app_id
-  );
-
-  info!("Fetching haptic definitions from URL: {}", url);
-
-  let response = reqwest::get(url).await?;
-  let response_body = response
-    .json::<SdkApiResponseV3<HapticDefinitionsMessage>>()
-    .await
-    .context("Failed to parse haptic definitions response")?;
-
-  response_body
-    .message
-    .context("No message in haptic definitions response")
-}
-
 #[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
 #[getset(get = "pub", set_with = "pub")]
@@ -101,12 +100,23 @@ impl HapticDefinitionsMessage {
   }
 }
 
-#[derive(Derivative, Getters)]
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HapticDefinitionMapping {
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  id: Option<String>,
+  /// Deployment of the workspace the mapping was last published with.
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  deploy_id: Option<String>,
   enable: Option<bool>,
 
   #[cfg_attr(
@@ -123,6 +133,34 @@ pub struct HapticDefinitionMapping {
 
   #[cfg_attr(feature = "serde", serde(default))]
   tact_file_patterns: Vec<HapticDefinitionTactFilePattern>,
-  // #[serde(default)]
-  // audio_file_patterns: Option<Vec<HapticDefinitionAudioFilePattern>>,
+  #[cfg_attr(feature = "serde", serde(default))]
+  audio_file_patterns: Vec<HapticDefinitionAudioFilePattern>,
+}
+
+impl HapticDefinitionMapping {
+  pub fn new(key: String, event_time: u32) -> Self {
+    Self {
+      id: None,
+      deploy_id: None,
+      enable: None,
+      intensity: None,
+      key,
+      category: None,
+      description: None,
+      update_time: None,
+      event_time,
+      tact_file_patterns: vec![],
+      audio_file_patterns: vec![],
+    }
+  }
+}
+
+/// Whether the id can be used as a directory name as is, i.e. it cannot escape its parent
+/// directory.
+#[cfg(feature = "serde")]
+pub(crate) fn is_valid_path_segment(id: &str) -> bool {
+  !id.is_empty()
+    && id
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
@@ -0,0 +1,169 @@
+use anyhow::{Context, bail, ensure};
+use derivative::Derivative;
+use getset::Getters;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{
+  DevicePosition, HapticDefinitionMapping, HapticDefinitionTactFilePattern,
+  HapticDefinitionsMessage, TactFile, TactFileProject, is_valid_path_segment,
+};
+
+/// Workspaces kept as plain `.tact` files instead of the bHaptics cloud, a directory per
+/// workspace: `<root>/<workspace id>/<event key>[_<position>].tact`.
+///
+/// Every event key becomes a mapping with a pattern per file. The position suffix is either
+/// a position of the haptic definitions (`Vest`, `LeftArm`, `Face`, ...) or a mode key
+/// (`ForearmL`, `GloveR`, ...), anything else is a part of the key. Without it the position
+/// is taken from the modes of the file.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+pub struct LocalDefinitions {
+  root: PathBuf,
+}
+
+impl LocalDefinitions {
+  pub fn new(root: PathBuf) -> Self {
+    Self { root }
+  }
+
+  /// Whether there is a directory of the workspace.
+  pub fn contains(&self, workspace_id: &str) -> bool {
+    is_valid_path_segment(workspace_id) && self.root.join(workspace_id).is_dir()
+  }
+
+  /// Assembles the definitions of the workspace, the events play as long as their longest
+  /// pattern.
+  pub fn load(&self, workspace_id: &str) -> anyhow::Result<HapticDefinitionsMessage> {
+    ensure!(
+      is_valid_path_segment(workspace_id),
+      "Invalid workspace id: {workspace_id:?}"
+    );
+    let dir = self.root.join(workspace_id);
+
+    let mut paths = fs::read_dir(&dir)
+      .with_context(|| format!("Failed to read {}", dir.display()))?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<Result<Vec<_>, _>>()?;
+    paths.retain(|path| {
+      path
+        .extension()
+        .is_some_and(|extension| extension == "tact")
+    });
+    paths.sort();
+
+    let mut events = BTreeMap::<String, Vec<HapticDefinitionTactFilePattern>>::new();
+    for path in paths {
+      let (key, position) = parse_file_name(&path)?;
+      let project = read_project(&path)?;
+      let position = match position {
+        Some(position) => position,
+        None => {
+          pattern_position(&project).with_context(|| format!("No effects in {}", path.display()))?
+        }
+      };
+
+      let patterns = events.entry(key.clone()).or_default();
+      if patterns
+        .iter()
+        .any(|pattern| *pattern.position() == position)
+      {
+        bail!("Duplicate {position} pattern of {key} in {}", dir.display());
+      }
+      patterns.push(HapticDefinitionTactFilePattern::new(position, project));
+    }
+
+    let mappings = events
+      .into_iter()
+      .map(|(key, patterns)| {
+        let event_time = patterns
+          .iter()
+          .map(|pattern| pattern.tact_file().duration_millis())
+          .max()
+          .unwrap_or(0);
+
+        HapticDefinitionMapping::new(key, event_time)
+          .with_enable(Some(true))
+          .with_tact_file_patterns(patterns)
+      })
+      .collect();
+
+    Ok(
+      HapticDefinitionsMessage::new(mappings)
+        .with_id(Some(workspace_id.to_string()))
+        .with_name(Some(workspace_id.to_string()))
+        .with_workspace_id(Some(workspace_id.to_string())),
+    )
+  }
+}
+
+/// Event key and position of the `<event key>[_<position>].tact` file.
+fn parse_file_name(path: &Path) -> anyhow::Result<(String, Option<DevicePosition>)> {
+  let stem = path
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .with_context(|| format!("Invalid file name {}", path.display()))?;
+
+  if let Some((key, suffix)) = stem.rsplit_once('_')
+    && !key.is_empty()
+    && let Some(position) = crate::tact::pattern_position::from_name(suffix)
+  {
+    return Ok((key.to_string(), Some(position)));
+  }
+
+  Ok((stem.to_string(), None))
+}
+
+fn read_project(path: &Path) -> anyhow::Result<TactFileProject> {
+  let json =
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+  let tact_file =
+    TactFile::from_json(&json).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+  Ok(tact_file.project().clone())
+}
+
+/// Position of the haptic definitions the modes of the project belong to, e.g. `Vest` for
+/// `VestFront`.
+fn pattern_position(project: &TactFileProject) -> Option<DevicePosition> {
+  let position = match project.positions().into_iter().next()? {
+    DevicePosition::VestFront | DevicePosition::VestBack => DevicePosition::Vest,
+    DevicePosition::ForearmR => DevicePosition::ForearmL,
+    DevicePosition::HandR => DevicePosition::HandL,
+    DevicePosition::FootR => DevicePosition::FootL,
+    DevicePosition::Tactal => DevicePosition::Head,
+    other => other,
+  };
+
+  Some(position)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn file_names_split_into_key_and_position() {
+    let parse = |name: &str| parse_file_name(Path::new(name)).unwrap();
+
+    assert_eq!(
+      parse("hit_Vest.tact"),
+      ("hit".to_string(), Some(DevicePosition::Vest))
+    );
+    assert_eq!(
+      parse("recoil_LeftArm.tact"),
+      ("recoil".to_string(), Some(DevicePosition::ForearmL))
+    );
+    assert_eq!(
+      parse("shoot_GloveR.tact"),
+      ("shoot".to_string(), Some(DevicePosition::GloveR))
+    );
+    assert_eq!(
+      parse("arm_recoil_t2_r.tact"),
+      ("arm_recoil_t2_r".to_string(), None)
+    );
+    assert_eq!(parse("_Vest.tact"), ("_Vest".to_string(), None));
+  }
+}
@@ -0,0 +1,161 @@
+use derivative::Derivative;
+use getset::Getters;
+use std::collections::HashMap;
+
+use crate::{DevicePosition, HapticDefinitionMapping, HapticDefinitionsMessage};
+
+/// Haptic definitions indexed by the mapping key.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+pub struct HapticDefinitionsRegistry {
+  #[get = "pub"]
+  definitions: HapticDefinitionsMessage,
+
+  /// Index of the mapping of every key, the first one wins for a duplicate key.
+  by_key: HashMap<String, usize>,
+}
+
+impl HapticDefinitionsRegistry {
+  pub fn new(definitions: HapticDefinitionsMessage) -> Self {
+    let mut by_key = HashMap::new();
+    for (i, mapping) in definitions.haptic_mappings().iter().enumerate() {
+      by_key.entry(mapping.key().clone()).or_insert(i);
+    }
+
+    Self {
+      definitions,
+      by_key,
+    }
+  }
+
+  pub fn get(&self, key: &str) -> Option<&HapticDefinitionMapping> {
+    let i = *self.by_key.get(key)?;
+    self.definitions.haptic_mappings().get(i)
+  }
+
+  pub fn contains(&self, key: &str) -> bool {
+    self.by_key.contains_key(key)
+  }
+
+  /// Number of the distinct keys.
+  pub fn len(&self) -> usize {
+    self.by_key.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.by_key.is_empty()
+  }
+
+  /// Mappings in the order of the definitions, without the duplicate keys.
+  pub fn mappings(&self) -> impl Iterator<Item = &HapticDefinitionMapping> {
+    self
+      .definitions
+      .haptic_mappings()
+      .iter()
+      .enumerate()
+      .filter(|(i, mapping)| self.by_key.get(mapping.key()) == Some(i))
+      .map(|(_, mapping)| mapping)
+  }
+
+  pub fn enabled(&self) -> impl Iterator<Item = &HapticDefinitionMapping> {
+    self.mappings().filter(|mapping| mapping.is_enabled())
+  }
+
+  pub fn in_category<'a>(
+    &'a self,
+    category: &'a str,
+  ) -> impl Iterator<Item = &'a HapticDefinitionMapping> {
+    self
+      .mappings()
+      .filter(move |mapping| mapping.category().as_deref() == Some(category))
+  }
+
+  /// Categories used by the mappings, sorted.
+  pub fn categories(&self) -> Vec<&str> {
+    let mut categories = self
+      .mappings()
+      .filter_map(|mapping| mapping.category().as_deref())
+      .collect::<Vec<_>>();
+    categories.sort_unstable();
+    categories.dedup();
+    categories
+  }
+
+  /// See [HapticDefinitionMapping::positions], `None` for an unknown key.
+  pub fn positions(&self, key: &str) -> Option<Vec<DevicePosition>> {
+    self.get(key).map(HapticDefinitionMapping::positions)
+  }
+}
+
+impl From<HapticDefinitionsMessage> for HapticDefinitionsRegistry {
+  fn from(definitions: HapticDefinitionsMessage) -> Self {
+    Self::new(definitions)
+  }
+}
+
+impl HapticDefinitionMapping {
+  /// Mappings without the `enable` field are enabled.
+  pub fn is_enabled(&self) -> bool {
+    self.enable().unwrap_or(true)
+  }
+
+  /// Positions played by the tact and audio patterns of the mapping, sorted.
+  pub fn positions(&self) -> Vec<DevicePosition> {
+    let tact_positions = self
+      .tact_file_patterns()
+      .iter()
+      .flat_map(|pattern| pattern.tact_file().positions());
+    let audio_positions = self
+      .audio_file_patterns()
+      .iter()
+      .flat_map(|pattern| pattern.clip().patterns().keys().cloned());
+
+    let mut positions = tact_positions.chain(audio_positions).collect::<Vec<_>>();
+    positions.sort_by_key(ToString::to_string);
+    positions.dedup();
+    positions
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn registry() -> HapticDefinitionsRegistry {
+    HapticDefinitionsRegistry::new(HapticDefinitionsMessage::new(vec![
+      HapticDefinitionMapping::new("hit".to_string(), 500)
+        .with_category(Some("combat".to_string())),
+      HapticDefinitionMapping::new("heal".to_string(), 0).with_enable(Some(false)),
+      HapticDefinitionMapping::new("hit".to_string(), 100),
+      HapticDefinitionMapping::new("shoot".to_string(), 100)
+        .with_category(Some("combat".to_string())),
+    ]))
+  }
+
+  #[test]
+  fn looks_up_mappings_by_key() {
+    let registry = registry();
+
+    assert_eq!(registry.len(), 3);
+    assert_eq!(
+      registry.get("hit").map(|mapping| *mapping.event_time()),
+      Some(500)
+    );
+    assert!(registry.get("miss").is_none());
+    assert_eq!(registry.positions("heal"), Some(vec![]));
+    assert_eq!(registry.positions("miss"), None);
+  }
+
+  fn keys<'a>(mappings: impl Iterator<Item = &'a HapticDefinitionMapping>) -> Vec<&'a str> {
+    mappings.map(|mapping| mapping.key().as_str()).collect()
+  }
+
+  #[test]
+  fn filters_mappings() {
+    let registry = registry();
+
+    assert_eq!(keys(registry.enabled()), vec!["hit", "shoot"]);
+    assert_eq!(keys(registry.in_category("combat")), vec!["hit", "shoot"]);
+    assert_eq!(registry.categories(), vec!["combat"]);
+  }
+}
@@ -0,0 +1,32 @@
+use super::{EffectWindow, PositionTimeline, ticks_between};
+use crate::{DevicePosition, EffectDotMode, Layout};
+
+/// Every point of a dot feedback vibrates its motor for the whole `startTime..endTime` span,
+/// shaped by the feedback `playbackType`. Points placed by `x`/`y` vibrate the nearest motor.
+pub(super) fn render(
+  dot_mode: &EffectDotMode,
+  layout: &Layout,
+  position: &DevicePosition,
+  window: EffectWindow,
+  tick_millis: u32,
+  target: &mut PositionTimeline,
+) {
+  for feedback in dot_mode.feedback() {
+    let (from, to) = window.clip(*feedback.start_time(), *feedback.end_time());
+
+    for tick in ticks_between(from, to, tick_millis) {
+      let envelope = feedback.playback_type().envelope_between(
+        *feedback.start_time(),
+        *feedback.end_time(),
+        window.relative_millis(tick, tick_millis),
+      );
+
+      for point in feedback.point_list() {
+        let Some(index) = point.index_in(layout, position) else {
+          continue;
+        };
+        target.merge_intensity(tick, index as usize, point.intensity() * envelope);
+      }
+    }
+  }
+}
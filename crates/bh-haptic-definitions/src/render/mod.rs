@@ -0,0 +1,409 @@
+mod dot;
+mod path;
+
+use derivative::Derivative;
+use getset::{Getters, WithSetters};
+use std::collections::HashMap;
+use tracing::*;
+
+use crate::{
+  DevicePosition, EffectFeedbackPlaybackType, EffectMode, HapticDefinitionMapping, HapticEffect,
+  Layout, LayoutPoint, TactFileProject, default_motor_count,
+};
+
+pub const fn default_tick_millis() -> u32 {
+  20
+}
+
+#[derive(Derivative, Getters, WithSetters)]
+#[derivative(Debug, Clone, PartialEq, Default)]
+#[getset(get = "pub", set_with = "pub")]
+pub struct RenderOptions {
+  /// Sampling interval of the rendered timeline.
+  #[derivative(Default(value = "default_tick_millis()"))]
+  tick_millis: u32,
+
+  /// Number of the nearest motors a path mode point is spread over.
+  #[derivative(Default(value = "default_motor_count()"))]
+  path_motor_count: usize,
+}
+
+/// Pattern sampled into per-device, per-motor intensities at a fixed tick rate.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct HapticTimeline {
+  tick_millis: u32,
+  duration_millis: u32,
+  positions: HashMap<DevicePosition, PositionTimeline>,
+}
+
+impl HapticTimeline {
+  pub fn new(tick_millis: u32, duration_millis: u32) -> Self {
+    Self {
+      tick_millis: tick_millis.max(1),
+      duration_millis,
+      positions: HashMap::new(),
+    }
+  }
+
+  pub fn frame_count(&self) -> usize {
+    self.duration_millis.div_ceil(self.tick_millis) as usize
+  }
+
+  /// Index of the frame, which is playing at the given time.
+  pub fn tick_at(&self, millis: u32) -> usize {
+    (millis / self.tick_millis) as usize
+  }
+
+  pub fn position(&self, position: &DevicePosition) -> Option<&PositionTimeline> {
+    self.positions.get(position)
+  }
+
+  /// Replaces the timeline of the position, it is fit to the frame count of this timeline.
+  pub fn set_position(&mut self, position: DevicePosition, mut timeline: PositionTimeline) {
+    timeline
+      .frames
+      .resize(self.frame_count(), vec![0.0; timeline.motor_count]);
+    self.positions.insert(position, timeline);
+  }
+
+  /// Returns the timeline of the position, inserting a silent one if missing.
+  pub fn position_mut(
+    &mut self,
+    position: DevicePosition,
+    motor_count: usize,
+  ) -> &mut PositionTimeline {
+    let frame_count = self.frame_count();
+    let timeline = self
+      .positions
+      .entry(position)
+      .or_insert_with(|| PositionTimeline::new(motor_count, frame_count));
+    timeline.resize_motors(motor_count);
+    timeline
+  }
+
+  /// Plays `other` along with this timeline, overlapping motors keep the strongest intensity.
+  /// Both timelines are expected to have the same `tick_millis`.
+  pub fn merge(&mut self, other: &HapticTimeline) {
+    if other.duration_millis > self.duration_millis {
+      self.duration_millis = other.duration_millis;
+      let frame_count = self.frame_count();
+      for timeline in self.positions.values_mut() {
+        timeline
+          .frames
+          .resize(frame_count, vec![0.0; timeline.motor_count]);
+      }
+    }
+
+    for (position, timeline) in &other.positions {
+      let target = self.position_mut(position.clone(), timeline.motor_count);
+      for (tick, frame) in timeline.frames.iter().enumerate() {
+        for (motor, intensity) in frame.iter().enumerate() {
+          target.merge_intensity(tick, motor, *intensity);
+        }
+      }
+    }
+  }
+}
+
+/// Intensities (`0.0..=1.0`) of every motor of a single device, one frame per tick.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq)]
+#[get = "pub"]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PositionTimeline {
+  motor_count: usize,
+  frames: Vec<Vec<f64>>,
+}
+
+impl PositionTimeline {
+  pub fn new(motor_count: usize, frame_count: usize) -> Self {
+    Self {
+      motor_count,
+      frames: vec![vec![0.0; motor_count]; frame_count],
+    }
+  }
+
+  pub fn frame(&self, tick: usize) -> Option<&[f64]> {
+    self.frames.get(tick).map(Vec::as_slice)
+  }
+
+  pub fn intensity(&self, tick: usize, motor: usize) -> f64 {
+    self
+      .frames
+      .get(tick)
+      .and_then(|frame| frame.get(motor))
+      .copied()
+      .unwrap_or(0.0)
+  }
+
+  pub fn set_intensity(&mut self, tick: usize, motor: usize, intensity: f64) {
+    self.resize_motors(motor + 1);
+    if let Some(frame) = self.frames.get_mut(tick) {
+      frame[motor] = intensity.clamp(0.0, 1.0);
+    }
+  }
+
+  /// Overlapping feedback on the same motor keeps the strongest intensity.
+  pub fn merge_intensity(&mut self, tick: usize, motor: usize, intensity: f64) {
+    let current = self.intensity(tick, motor);
+    if intensity > current {
+      self.set_intensity(tick, motor, intensity);
+    }
+  }
+
+  pub fn is_silent(&self) -> bool {
+    self
+      .frames
+      .iter()
+      .flatten()
+      .all(|intensity| *intensity <= 0.0)
+  }
+
+  fn resize_motors(&mut self, motor_count: usize) {
+    if motor_count <= self.motor_count {
+      return;
+    }
+    self.motor_count = motor_count;
+    for frame in &mut self.frames {
+      frame.resize(motor_count, 0.0);
+    }
+  }
+}
+
+/// Absolute time span of an effect within the project.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EffectWindow {
+  start: u32,
+  duration: u32,
+}
+
+impl EffectWindow {
+  /// Converts an effect-relative span into the absolute one, cut to the effect end and to the end
+  /// of time.
+  pub(crate) fn clip(&self, from: u32, to: u32) -> (u32, u32) {
+    let to = to.min(self.duration);
+    (
+      self.start.saturating_add(from.min(to)),
+      self.start.saturating_add(to),
+    )
+  }
+
+  /// Time of the tick relative to the effect start.
+  pub(crate) fn relative_millis(&self, tick: usize, tick_millis: u32) -> f64 {
+    (tick as u64 * tick_millis as u64).saturating_sub(self.start as u64) as f64
+  }
+}
+
+impl EffectFeedbackPlaybackType {
+  /// Intensity multiplier at the given progress (`0.0..=1.0`) through the feedback.
+  pub fn envelope(&self, progress: f64) -> f64 {
+    let progress = progress.clamp(0.0, 1.0);
+
+    match self {
+      EffectFeedbackPlaybackType::None => 1.0,
+      EffectFeedbackPlaybackType::FadeIn => progress,
+      EffectFeedbackPlaybackType::FadeOut => 1.0 - progress,
+      EffectFeedbackPlaybackType::FadeInOut => 1.0 - (2.0 * progress - 1.0).abs(),
+    }
+  }
+
+  /// Envelope over the authored `from..to` span, so the end of the effect cutting the feedback
+  /// keeps its shape. [crate::HapticEffect::trimmed] moves the span itself, re-shaping it.
+  pub(crate) fn envelope_between(&self, from: u32, to: u32, millis: f64) -> f64 {
+    let span = to.saturating_sub(from) as f64;
+    let progress = if span > 0.0 {
+      (millis - from as f64) / span
+    } else {
+      1.0
+    };
+
+    self.envelope(progress)
+  }
+}
+
+impl TactFileProject {
+  /// Renders all enabled tracks into a [HapticTimeline].
+  ///
+  /// Positions missing in the `layouts` are rendered with the catalogue layouts.
+  pub fn render(&self, options: &RenderOptions) -> HapticTimeline {
+    let mut timeline = HapticTimeline::new(*options.tick_millis(), self.duration_millis());
+    let layout = self.layout().with_default_points(self.positions());
+
+    for track in self.tracks() {
+      if !track.enable().unwrap_or(true) {
+        continue;
+      }
+
+      for effect in track.effects() {
+        render_effect(&layout, effect, options, &mut timeline);
+      }
+    }
+
+    timeline
+  }
+
+  /// End of the last effect of the enabled tracks.
+  pub fn duration_millis(&self) -> u32 {
+    self
+      .tracks()
+      .iter()
+      .filter(|track| track.enable().unwrap_or(true))
+      .flat_map(|track| track.effects())
+      .map(|effect| {
+        effect
+          .start_millis()
+          .saturating_add(effect.duration_millis())
+      })
+      .max()
+      .unwrap_or(0)
+  }
+}
+
+impl HapticDefinitionMapping {
+  /// Renders all tact patterns of the mapping into a single [HapticTimeline].
+  pub fn render(&self, options: &RenderOptions) -> HapticTimeline {
+    let mut timeline = HapticTimeline::new(*options.tick_millis(), 0);
+
+    for pattern in self.tact_file_patterns() {
+      timeline.merge(&pattern.tact_file().render(options));
+    }
+
+    timeline
+  }
+}
+
+impl HapticEffect {
+  pub fn start_millis(&self) -> u32 {
+    self.start_time().unwrap_or(0)
+  }
+
+  /// Length of the effect: `offsetTime` if present, otherwise the end of its latest feedback.
+  pub fn duration_millis(&self) -> u32 {
+    if let Some(offset_time) = self.offset_time() {
+      return *offset_time;
+    }
+
+    self
+      .modes()
+      .values()
+      .map(|mode| match mode {
+        EffectMode::DotMode { dot_mode, .. } => dot_mode
+          .feedback()
+          .iter()
+          .map(|feedback| *feedback.end_time())
+          .max()
+          .unwrap_or(0),
+        EffectMode::PathMode { path_mode, .. } => path_mode
+          .feedback()
+          .iter()
+          .flat_map(|feedback| feedback.point_list().last())
+          .map(|point| point.time_millis())
+          .max()
+          .unwrap_or(0),
+      })
+      .max()
+      .unwrap_or(0)
+  }
+}
+
+impl Layout {
+  pub fn points(&self, position: &DevicePosition) -> Option<&[LayoutPoint]> {
+    self.layouts().as_ref()?.get(position).map(Vec::as_slice)
+  }
+
+  /// Number of motors of the position, as listed in `layouts`.
+  pub fn motor_count(&self, position: &DevicePosition) -> Option<usize> {
+    self
+      .points(position)?
+      .iter()
+      .map(|point| *point.index() as usize + 1)
+      .max()
+  }
+}
+
+fn render_effect(
+  layout: &Layout,
+  effect: &HapticEffect,
+  options: &RenderOptions,
+  timeline: &mut HapticTimeline,
+) {
+  let window = EffectWindow {
+    start: effect.start_millis(),
+    duration: effect.duration_millis(),
+  };
+  let tick_millis = *timeline.tick_millis();
+
+  for (position, mode) in effect.modes() {
+    if position.is_unknown() {
+      warn!("Skipping effect mode for unknown position: {position}");
+      continue;
+    }
+    let motor_count = layout.motor_count(position).unwrap_or(0);
+    let target = timeline.position_mut(position.clone(), motor_count);
+
+    match mode {
+      EffectMode::DotMode { dot_mode, .. } => {
+        dot::render(dot_mode, layout, position, window, tick_millis, target)
+      }
+      EffectMode::PathMode { path_mode, .. } => {
+        if layout.points(position).is_none() {
+          warn!("Skipping path mode without layout points for {position}");
+          continue;
+        }
+        path::render(
+          path_mode,
+          layout,
+          position,
+          options.path_motor_count,
+          window,
+          tick_millis,
+          target,
+        );
+      }
+    }
+  }
+}
+
+/// Weights of the `count` motors nearest to the `(x, y)` point.
+///
+/// The nearest motor gets the full weight, the others get less proportionally to how much
+/// farther they are, so a point right on top of a motor vibrates only that one.
+pub(crate) fn spread(points: &[LayoutPoint], x: f64, y: f64, count: usize) -> Vec<(usize, f64)> {
+  let mut nearest = points
+    .iter()
+    .map(|point| {
+      (
+        *point.index() as usize,
+        (point.x() - x).hypot(point.y() - y),
+      )
+    })
+    .collect::<Vec<_>>();
+  nearest.sort_by(|a, b| a.1.total_cmp(&b.1));
+  nearest.truncate(count.max(1));
+
+  let Some((_, min_distance)) = nearest.first().copied() else {
+    return vec![];
+  };
+
+  nearest
+    .into_iter()
+    .map(|(index, distance)| {
+      let weight = if distance <= f64::EPSILON {
+        1.0
+      } else {
+        min_distance / distance
+      };
+      (index, weight)
+    })
+    .collect()
+}
+
+/// Ticks, which start within `from..to`.
+pub(crate) fn ticks_between(from: u32, to: u32, tick_millis: u32) -> impl Iterator<Item = usize> {
+  (from.div_ceil(tick_millis)..to.div_ceil(tick_millis)).map(|tick| tick as usize)
+}
@@ -0,0 +1,143 @@
+use super::{EffectWindow, PositionTimeline, spread, ticks_between};
+use crate::{DevicePosition, EffectPathMode, EffectPathModeMovingPattern, Layout};
+
+/// Point of the path with its coordinates resolved through the layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PathPoint {
+  x: f64,
+  y: f64,
+  time: u32,
+  intensity: f64,
+}
+
+/// Point moving along the path, sampled at a single moment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PathSample {
+  x: f64,
+  y: f64,
+  intensity: f64,
+}
+
+impl PathSample {
+  fn lerp(from: &PathPoint, to: &PathPoint, f: f64) -> Self {
+    Self {
+      x: from.x + (to.x - from.x) * f,
+      y: from.y + (to.y - from.y) * f,
+      intensity: from.intensity + (to.intensity - from.intensity) * f,
+    }
+  }
+}
+
+/// Moves a point along every feedback path and spreads its intensity over the nearest motors.
+pub(super) fn render(
+  path_mode: &EffectPathMode,
+  layout: &Layout,
+  position: &DevicePosition,
+  motor_count: usize,
+  window: EffectWindow,
+  tick_millis: u32,
+  target: &mut PositionTimeline,
+) {
+  let layout_points = layout.points(position).unwrap_or_default();
+
+  for feedback in path_mode.feedback() {
+    // points, which cannot be placed onto the layout, are left out of the path
+    let points = feedback
+      .point_list()
+      .iter()
+      .filter_map(|point| {
+        let (x, y) = point.coordinates_in(layout, position)?;
+        Some(PathPoint {
+          x,
+          y,
+          time: point.time_millis(),
+          intensity: *point.intensity(),
+        })
+      })
+      .collect::<Vec<_>>();
+    let points = points.as_slice();
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+      continue;
+    };
+
+    // a single point does not move, it stays until the effect ends
+    let end = if points.len() == 1 {
+      window.duration
+    } else {
+      last.time
+    };
+    let (from, to) = window.clip(first.time, end);
+
+    for tick in ticks_between(from, to, tick_millis) {
+      let millis = window.relative_millis(tick, tick_millis);
+      let sample = match feedback.moving_pattern() {
+        EffectPathModeMovingPattern::ConstSpeed => sample_const_speed(points, millis),
+        EffectPathModeMovingPattern::ConstTdm => sample_const_tdm(points, millis),
+      };
+      let envelope = feedback
+        .playback_type()
+        .envelope_between(first.time, end, millis);
+
+      for (motor, weight) in spread(layout_points, sample.x, sample.y, motor_count) {
+        target.merge_intensity(tick, motor, sample.intensity * weight * envelope);
+      }
+    }
+  }
+}
+
+/// `CONST_TDM`: every segment lasts exactly as long as the `time` of its points says.
+fn sample_const_tdm(points: &[PathPoint], millis: f64) -> PathSample {
+  let Some(segment) = points
+    .windows(2)
+    .find(|segment| millis <= segment[1].time as f64)
+  else {
+    let last = &points[points.len() - 1];
+    return PathSample::lerp(last, last, 0.0);
+  };
+
+  let (from, to) = (&segment[0], &segment[1]);
+  let span = (to.time as f64 - from.time as f64).max(0.0);
+  let f = if span > 0.0 {
+    ((millis - from.time as f64) / span).clamp(0.0, 1.0)
+  } else {
+    1.0
+  };
+
+  PathSample::lerp(from, to, f)
+}
+
+/// `CONST_SPEED`: the point travels the whole path at a constant speed between the first and
+/// the last point time, regardless of the time of the points in between.
+fn sample_const_speed(points: &[PathPoint], millis: f64) -> PathSample {
+  let first = &points[0];
+  let last = &points[points.len() - 1];
+
+  let lengths = points
+    .windows(2)
+    .map(|segment| (segment[1].x - segment[0].x).hypot(segment[1].y - segment[0].y))
+    .collect::<Vec<_>>();
+  let total_length = lengths.iter().sum::<f64>();
+  let total_time = last.time as f64 - first.time as f64;
+
+  // nothing to travel, time is the only thing that can move the point
+  if total_length <= f64::EPSILON || total_time <= 0.0 {
+    return sample_const_tdm(points, millis);
+  }
+
+  let progress = ((millis - first.time as f64) / total_time).clamp(0.0, 1.0);
+  let mut distance = progress * total_length;
+
+  for (segment, length) in points.windows(2).zip(&lengths) {
+    if distance <= *length {
+      let f = if *length > 0.0 {
+        distance / length
+      } else {
+        1.0
+      };
+      return PathSample::lerp(&segment[0], &segment[1], f);
+    }
+    distance -= length;
+  }
+
+  PathSample::lerp(last, last, 0.0)
+}
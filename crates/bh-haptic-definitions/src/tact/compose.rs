@@ -0,0 +1,305 @@
+//! Composition of tact projects: layering them, playing them one after another and cutting them
+//! in time.
+//!
+//! Composed projects keep all the effects as they were authored, so effects overlapping on the
+//! same motor play the strongest of their intensities, see
+//! [crate::PositionTimeline::merge_intensity].
+
+use std::collections::HashMap;
+
+use crate::{EffectDotMode, EffectPathMode, EffectPoint, HapticEffect, TactFileProject, Track};
+
+impl TactFileProject {
+  /// Plays the `others` along with this project, their tracks are added after its own ones.
+  ///
+  /// Layout points of the positions missing in this project are taken from the others.
+  pub fn layered<'a>(&self, others: impl IntoIterator<Item = &'a TactFileProject>) -> Self {
+    let mut tracks = self.tracks().clone();
+    let mut layouts = self.layout().layouts().clone();
+
+    for other in others {
+      tracks.extend(other.tracks().iter().cloned());
+
+      if let Some(other_layouts) = other.layout().layouts() {
+        let layouts = layouts.get_or_insert_with(HashMap::new);
+        for (position, points) in other_layouts {
+          layouts
+            .entry(position.clone())
+            .or_insert_with(|| points.clone());
+        }
+      }
+    }
+
+    let layout = self.layout().clone().with_layouts(layouts);
+    self.clone().with_tracks(tracks).with_layout(layout)
+  }
+
+  /// Plays `next` `gap_millis` after the end of this project.
+  pub fn appended(&self, next: &TactFileProject, gap_millis: u32) -> Self {
+    let start = self.duration_millis().saturating_add(gap_millis);
+    self.layered([&next.shifted(i64::from(start))])
+  }
+
+  /// Moves every effect by `millis`, a negative shift cuts off what would start before zero.
+  pub fn shifted(&self, millis: i64) -> Self {
+    let tracks = self
+      .tracks()
+      .iter()
+      .map(|track| track.shifted(millis))
+      .collect();
+
+    self.clone().with_tracks(tracks)
+  }
+
+  /// Keeps only the `from..to` part, moved to start at zero, see [HapticEffect::trimmed].
+  pub fn trimmed(&self, from: u32, to: u32) -> Self {
+    let tracks = self
+      .tracks()
+      .iter()
+      .map(|track| track.trimmed(from, to))
+      .collect();
+
+    self.clone().with_tracks(tracks)
+  }
+
+  /// Plays the project `times` times in a row, `gap_millis` apart.
+  pub fn looped(&self, times: u32, gap_millis: u32) -> Self {
+    let period = self.duration_millis().saturating_add(gap_millis);
+    let tracks = self
+      .tracks()
+      .iter()
+      .map(|track| track.repeated(times, period))
+      .collect();
+
+    self.clone().with_tracks(tracks)
+  }
+}
+
+impl Track {
+  /// End of the last effect.
+  pub fn duration_millis(&self) -> u32 {
+    self
+      .effects()
+      .iter()
+      .map(|effect| {
+        effect
+          .start_millis()
+          .saturating_add(effect.duration_millis())
+      })
+      .max()
+      .unwrap_or(0)
+  }
+
+  /// See [TactFileProject::shifted].
+  pub fn shifted(&self, millis: i64) -> Self {
+    if millis < 0 {
+      let from = u32::try_from(millis.unsigned_abs()).unwrap_or(u32::MAX);
+      return self.trimmed(from, u32::MAX);
+    }
+
+    let millis = u32::try_from(millis).unwrap_or(u32::MAX);
+    let effects = self
+      .effects()
+      .iter()
+      .map(|effect| effect.delayed(millis))
+      .collect();
+
+    self.clone().with_effects(effects)
+  }
+
+  /// See [TactFileProject::trimmed].
+  pub fn trimmed(&self, from: u32, to: u32) -> Self {
+    let effects = self
+      .effects()
+      .iter()
+      .filter_map(|effect| effect.trimmed(from, to))
+      .collect();
+
+    self.clone().with_effects(effects)
+  }
+
+  /// Repeats the effects `times` times, every repetition starts `period_millis` after the
+  /// previous one.
+  pub fn repeated(&self, times: u32, period_millis: u32) -> Self {
+    let effects = (0..times)
+      .flat_map(|i| {
+        self
+          .effects()
+          .iter()
+          .map(move |effect| effect.delayed(i.saturating_mul(period_millis)))
+      })
+      .collect();
+
+    self.clone().with_effects(effects)
+  }
+}
+
+impl HapticEffect {
+  /// Starts the effect `millis` later, at the latest at `u32::MAX`.
+  pub fn delayed(&self, millis: u32) -> Self {
+    self
+      .clone()
+      .with_start_time(Some(self.start_millis().saturating_add(millis)))
+      .with_offset_time(Some(self.duration_millis()))
+  }
+
+  /// Part of the effect within `from..to`, moved by `-from`. `None` if the effect is entirely
+  /// outside of it.
+  ///
+  /// Dot feedback is cut at the edges, path feedback gets points interpolated at them, so the
+  /// trimmed effect renders as the same window of the original one. Except for the fades of
+  /// the cut feedback: the files cannot start a fade midway, so they are re-shaped to the
+  /// remaining span, e.g. a `FADE_OUT` cut in half starts at full intensity again. A
+  /// `CONST_SPEED` path likewise keeps its speed only if it is not cut.
+  pub fn trimmed(&self, from: u32, to: u32) -> Option<Self> {
+    let start = self.start_millis();
+    let end = start.saturating_add(self.duration_millis());
+    let (cut_start, cut_end) = (start.max(from), end.min(to));
+    if cut_start >= cut_end {
+      return None;
+    }
+
+    // relative to the effect start, as the feedback times are
+    let (relative_from, relative_to) = (cut_start - start, cut_end - start);
+    let modes = self
+      .modes()
+      .iter()
+      .map(|(position, mode)| {
+        let mode = mode.mapped(
+          |dot_mode| trimmed_dot_mode(dot_mode, relative_from, relative_to),
+          |path_mode| trimmed_path_mode(path_mode, relative_from, relative_to),
+        );
+        (position.clone(), mode)
+      })
+      .collect();
+
+    Some(
+      self
+        .clone()
+        .with_start_time(Some(cut_start - from))
+        .with_offset_time(Some(cut_end - cut_start))
+        .with_modes(modes),
+    )
+  }
+}
+
+fn trimmed_dot_mode(dot_mode: &EffectDotMode, from: u32, to: u32) -> EffectDotMode {
+  let feedback = dot_mode
+    .feedback()
+    .iter()
+    .filter(|feedback| *feedback.start_time() < to && *feedback.end_time() > from)
+    .map(|feedback| {
+      let start_time = feedback.start_time().max(&from) - from;
+      let end_time = feedback.end_time().min(&to) - from;
+      feedback
+        .clone()
+        .with_start_time(start_time)
+        .with_end_time(end_time)
+    })
+    .collect();
+
+  dot_mode.clone().with_feedback(feedback)
+}
+
+fn trimmed_path_mode(path_mode: &EffectPathMode, from: u32, to: u32) -> EffectPathMode {
+  let feedback = path_mode
+    .feedback()
+    .iter()
+    .filter_map(|feedback| {
+      let points = trimmed_path(feedback.point_list(), from, to);
+      (!points.is_empty()).then(|| feedback.clone().with_point_list(points))
+    })
+    .collect();
+
+  path_mode.clone().with_feedback(feedback)
+}
+
+/// Points of the path within `from..=to`, with the path interpolated at the edges.
+fn trimmed_path(points: &[EffectPoint], from: u32, to: u32) -> Vec<EffectPoint> {
+  // a single point does not move, it stays until the effect ends
+  if let [point] = points {
+    let time = point.time_millis().max(from);
+    return if time <= to {
+      vec![point.clone().with_time(Some(time - from))]
+    } else {
+      vec![]
+    };
+  }
+
+  let mut trimmed = vec![];
+  for (i, point) in points.iter().enumerate() {
+    let time = point.time_millis();
+    let previous = i.checked_sub(1).map(|i| &points[i]);
+
+    if let Some(previous) = previous {
+      for edge in [from, to] {
+        if previous.time_millis() < edge && edge < time {
+          trimmed.push(interpolated(previous, point, edge));
+        }
+      }
+    }
+    if (from..=to).contains(&time) {
+      trimmed.push(point.clone());
+    }
+  }
+
+  trimmed
+    .into_iter()
+    .map(|point| {
+      let time = point.time_millis() - from;
+      point.with_time(Some(time))
+    })
+    .collect()
+}
+
+/// Point of the path between `from` and `to` at `time`. Points placed by their `index` cannot
+/// be interpolated, the earlier one is used for them.
+fn interpolated(from: &EffectPoint, to: &EffectPoint, time: u32) -> EffectPoint {
+  let span = f64::from(to.time_millis() - from.time_millis());
+  let f = f64::from(time - from.time_millis()) / span;
+  let lerp = |a: f64, b: f64| a + (b - a) * f;
+
+  let coordinates = from
+    .x()
+    .zip(*from.y())
+    .zip(to.x().zip(*to.y()))
+    .map(|((x1, y1), (x2, y2))| (lerp(x1, x2), lerp(y1, y2)));
+
+  let point = from
+    .clone()
+    .with_time(Some(time))
+    .with_intensity(lerp(*from.intensity(), *to.intensity()));
+  match coordinates {
+    Some((x, y)) => point.with_x(Some(x)).with_y(Some(y)),
+    None => point,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn path_is_interpolated_at_the_edges() {
+    let points = [
+      EffectPoint::path(0.0, 0.0, 0, 0.0),
+      EffectPoint::path(1.0, 1.0, 100, 1.0),
+    ];
+
+    assert_eq!(
+      trimmed_path(&points, 25, 75),
+      vec![
+        EffectPoint::path(0.25, 0.25, 0, 0.25),
+        EffectPoint::path(0.75, 0.75, 50, 0.75),
+      ]
+    );
+    assert_eq!(
+      trimmed_path(&points, 50, 200),
+      vec![
+        EffectPoint::path(0.5, 0.5, 0, 0.5),
+        EffectPoint::path(1.0, 1.0, 50, 1.0),
+      ]
+    );
+    assert_eq!(trimmed_path(&points, 150, 200), vec![]);
+  }
+}
@@ -1,11 +1,13 @@
 use derivative::Derivative;
-use getset::Getters;
+use getset::{Getters, WithSetters};
 
-use crate::EffectFeedbackPlaybackType;
+#[cfg(feature = "serde")]
+use crate::ExtraFields;
+use crate::{EffectFeedbackPlaybackType, EffectPoint};
 
-#[derive(Derivative, Getters)]
-#[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[derive(Derivative, Getters, WithSetters)]
+#[derivative(Debug, Clone, PartialEq, Eq, Default)]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct EffectDotMode {
@@ -13,31 +15,75 @@ pub struct EffectDotMode {
   dot_connected: bool,
 
   feedback: Vec<EffectDotModeFeedback>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
-#[derive(Derivative, Getters)]
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct EffectDotModeFeedback {
   start_time: u32,
   end_time: u32,
   playback_type: EffectFeedbackPlaybackType,
-  point_list: Vec<EffectDotModePoint>,
+  point_list: Vec<EffectPoint>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
-/// todo: this might be probably also be a enum, if `index` is missing, in the `PATH_MODE` `x` and `y` fields are present in the same struct
-/// See: [super::EffectPathModePoint]
-#[derive(Derivative, Getters)]
-#[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
-pub struct EffectDotModePoint {
-  /// reference to the `index` field of the [crate::LayoutPoint] in the [crate::Layout]
-  index: u32,
+impl EffectDotModeFeedback {
+  /// Points vibrating from `start_time` to `end_time` at a constant intensity.
+  pub fn new(start_time: u32, end_time: u32, point_list: Vec<EffectPoint>) -> Self {
+    Self {
+      start_time,
+      end_time,
+      playback_type: EffectFeedbackPlaybackType::None,
+      point_list,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+}
+
+impl EffectDotMode {
+  pub fn new(feedback: Vec<EffectDotModeFeedback>) -> Self {
+    Self {
+      dot_connected: false,
+      feedback,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  /// Replaces the points of every feedback with the ones returned by `remap`.
+  pub fn remapped(&self, remap: impl Fn(&[EffectPoint]) -> Vec<EffectPoint>) -> Self {
+    let feedback = self
+      .feedback
+      .iter()
+      .map(|feedback| EffectDotModeFeedback {
+        point_list: remap(&feedback.point_list),
+        ..feedback.clone()
+      })
+      .collect();
+
+    self.clone().with_feedback(feedback)
+  }
 
-  #[cfg_attr(feature = "serde", serde(deserialize_with = "serde_handy::de::to_f64"))]
-  intensity: f64,
+  /// Moves every point to the motor returned by `mirrored_index`.
+  pub fn flipped(&self, mirrored_index: impl Fn(u32) -> u32) -> Self {
+    self.remapped(|points| {
+      points
+        .iter()
+        .map(|point| point.flipped(&mirrored_index))
+        .collect()
+    })
+  }
 }
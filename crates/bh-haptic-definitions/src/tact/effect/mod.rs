@@ -1,45 +1,81 @@
 mod dot;
 mod path;
+mod point;
 
-use dot::*;
-use path::*;
+pub use dot::*;
+pub use path::*;
+pub use point::*;
 
 use derivative::Derivative;
-use getset::Getters;
+use getset::{Getters, WithSetters};
 use std::collections::HashMap;
 
-#[derive(Derivative, Getters)]
+#[cfg(feature = "serde")]
+use crate::ExtraFields;
+use crate::{DevicePosition, Layout, RetargetMethod, position_mapping};
+
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HapticEffect {
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   name: Option<String>,
 
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   offset_time: Option<u32>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   start_time: Option<u32>,
 
-  modes: HashMap<String, EffectMode>,
+  modes: HashMap<DevicePosition, EffectMode>,
+
+  /// E.g. `trackIndex`.
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
 /// From the clients I always receive both `dotMode` and `pathMode` fields, but from observation,
 /// only the one, selected by the `mode` JSON field is used, so I assume we might optimize their
 /// struct to enum.
+///
+/// The Designer does not open files missing either of them though, and switching the mode there
+/// brings the other one back, so the unused one is kept as it was read. It is not played and not
+/// transformed.
 #[derive(Derivative)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(tag = "mode", rename_all = "camelCase"))]
 pub enum EffectMode {
   #[cfg_attr(
     feature = "serde",
     serde(rename = "DOT_MODE", rename_all = "camelCase")
   )]
-  DotMode { dot_mode: EffectDotMode },
+  DotMode {
+    dot_mode: EffectDotMode,
+    #[cfg_attr(feature = "serde", serde(default, rename = "pathMode"))]
+    unused_path_mode: EffectPathMode,
+  },
   #[cfg_attr(
     feature = "serde",
     serde(rename = "PATH_MODE", rename_all = "camelCase")
   )]
-  PathMode { path_mode: EffectPathMode },
+  PathMode {
+    path_mode: EffectPathMode,
+    #[cfg_attr(feature = "serde", serde(default, rename = "dotMode"))]
+    unused_dot_mode: EffectDotMode,
+  },
 }
 
 #[derive(Derivative)]
@@ -55,3 +91,177 @@ pub enum EffectFeedbackPlaybackType {
   #[cfg_attr(feature = "serde", serde(rename = "FADE_IN_OUT"))]
   FadeInOut,
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for EffectMode {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    use serde::ser::SerializeStruct;
+
+    let (mode, dot_mode, path_mode) = match self {
+      EffectMode::DotMode {
+        dot_mode,
+        unused_path_mode,
+      } => ("DOT_MODE", dot_mode, unused_path_mode),
+      EffectMode::PathMode {
+        path_mode,
+        unused_dot_mode,
+      } => ("PATH_MODE", unused_dot_mode, path_mode),
+    };
+
+    let mut state = serializer.serialize_struct("EffectMode", 3)?;
+    state.serialize_field("mode", mode)?;
+    state.serialize_field("dotMode", dot_mode)?;
+    state.serialize_field("pathMode", path_mode)?;
+    state.end()
+  }
+}
+
+impl HapticEffect {
+  /// Effect without modes, lasting `offset_time` milliseconds from `start_time`.
+  pub fn new(start_time: u32, offset_time: u32) -> Self {
+    Self {
+      name: None,
+      offset_time: Some(offset_time),
+      start_time: Some(start_time),
+      modes: HashMap::new(),
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  /// Sets the mode of the position, replacing the previous one.
+  pub fn with_mode(mut self, position: DevicePosition, mode: impl Into<EffectMode>) -> Self {
+    self.modes.insert(position, mode.into());
+    self
+  }
+
+  /// Fields of the effect, which are not modelled by this crate.
+  #[cfg(feature = "serde")]
+  pub fn extra(&self) -> &ExtraFields {
+    &self.extra
+  }
+
+  /// Mirrors the effect across the body midline: modes of the left and right positions are
+  /// swapped, while patterns on the midline positions (vest, head) get their `x` flipped.
+  pub fn mirrored(&self, layout: &Layout) -> Self {
+    let modes = self
+      .modes
+      .iter()
+      .map(|(position, mode)| {
+        if position.is_left() || position.is_right() {
+          (position.mirrored(), mode.clone())
+        } else if position.is_unknown() {
+          (position.clone(), mode.clone())
+        } else {
+          (position.clone(), mode.flipped(layout, position))
+        }
+      })
+      .collect();
+
+    Self {
+      modes,
+      ..self.clone()
+    }
+  }
+}
+
+impl HapticEffect {
+  /// Moves the dot mode points onto the motors of the `target` layout, see
+  /// [crate::HapticTimeline::retargeted]. Path mode points keep their coordinates, the ones
+  /// placed by their `index` get the coordinates of their motor in the `source` layout.
+  pub fn retargeted(&self, source: &Layout, target: &Layout, method: RetargetMethod) -> Self {
+    let modes = self
+      .modes
+      .iter()
+      .map(|(position, mode)| {
+        let mapping = position_mapping(source, target, position, method);
+        let mode = match mapping {
+          Some(mapping) => mode.mapped(
+            |dot_mode| {
+              dot_mode.remapped(|points| {
+                let motors = points
+                  .iter()
+                  .filter_map(|point| {
+                    let index = point.index_in(source, position)?;
+                    Some((index as usize, *point.intensity()))
+                  })
+                  .collect::<Vec<_>>();
+
+                mapping
+                  .map(&motors)
+                  .into_iter()
+                  .map(|(motor, intensity)| EffectPoint::dot(motor as u32, intensity))
+                  .collect()
+              })
+            },
+            |path_mode| path_mode.remapped(|point| point.with_coordinates_in(source, position)),
+          ),
+          None => mode.clone(),
+        };
+
+        (position.clone(), mode)
+      })
+      .collect();
+
+    Self {
+      modes,
+      ..self.clone()
+    }
+  }
+}
+
+impl From<EffectDotMode> for EffectMode {
+  fn from(dot_mode: EffectDotMode) -> Self {
+    EffectMode::DotMode {
+      dot_mode,
+      unused_path_mode: EffectPathMode::default(),
+    }
+  }
+}
+
+impl From<EffectPathMode> for EffectMode {
+  fn from(path_mode: EffectPathMode) -> Self {
+    EffectMode::PathMode {
+      path_mode,
+      unused_dot_mode: EffectDotMode::default(),
+    }
+  }
+}
+
+impl EffectMode {
+  /// Replaces the played mode with the result of `dot` or `path`, the unused one is kept.
+  pub(crate) fn mapped(
+    &self,
+    dot: impl FnOnce(&EffectDotMode) -> EffectDotMode,
+    path: impl FnOnce(&EffectPathMode) -> EffectPathMode,
+  ) -> Self {
+    match self {
+      EffectMode::DotMode {
+        dot_mode,
+        unused_path_mode,
+      } => EffectMode::DotMode {
+        dot_mode: dot(dot_mode),
+        unused_path_mode: unused_path_mode.clone(),
+      },
+      EffectMode::PathMode {
+        path_mode,
+        unused_dot_mode,
+      } => EffectMode::PathMode {
+        path_mode: path(path_mode),
+        unused_dot_mode: unused_dot_mode.clone(),
+      },
+    }
+  }
+
+  /// Flips the pattern horizontally within the layout of the position.
+  pub fn flipped(&self, layout: &Layout, position: &DevicePosition) -> Self {
+    let mirrored_index = |index| layout.mirrored_index(position, index);
+    self.mapped(
+      |dot_mode| dot_mode.flipped(mirrored_index),
+      |path_mode| path_mode.flipped(mirrored_index),
+    )
+  }
+}
@@ -1,44 +1,81 @@
-use crate::EffectFeedbackPlaybackType;
+#[cfg(feature = "serde")]
+use crate::ExtraFields;
+use crate::{EffectFeedbackPlaybackType, EffectPoint};
 use derivative::Derivative;
-use getset::Getters;
+use getset::{Getters, WithSetters};
 
-#[derive(Derivative, Getters)]
-#[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[derive(Derivative, Getters, WithSetters)]
+#[derivative(Debug, Clone, PartialEq, Eq, Default)]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct EffectPathMode {
   feedback: Vec<EffectPathModeFeedback>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
-#[derive(Derivative, Getters)]
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct EffectPathModeFeedback {
   playback_type: EffectFeedbackPlaybackType,
   moving_pattern: EffectPathModeMovingPattern,
   visible: bool,
-  point_list: Vec<EffectPathModePoint>,
+  point_list: Vec<EffectPoint>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
-#[derive(Derivative, Getters)]
-#[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
-pub struct EffectPathModePoint {
-  #[cfg_attr(feature = "serde", serde(deserialize_with = "serde_handy::de::to_f64"))]
-  intensity: f64,
+impl EffectPathModeFeedback {
+  /// Visible path moving through the points at their `time`.
+  pub fn new(point_list: Vec<EffectPoint>) -> Self {
+    Self {
+      playback_type: EffectFeedbackPlaybackType::None,
+      moving_pattern: EffectPathModeMovingPattern::ConstTdm,
+      visible: true,
+      point_list,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+}
+
+impl EffectPathMode {
+  pub fn new(feedback: Vec<EffectPathModeFeedback>) -> Self {
+    Self {
+      feedback,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
 
-  time: u32,
+  /// Replaces every point of the feedback with `remap` of it.
+  pub fn remapped(&self, remap: impl Fn(&EffectPoint) -> EffectPoint) -> Self {
+    let feedback = self
+      .feedback
+      .iter()
+      .map(|feedback| EffectPathModeFeedback {
+        point_list: feedback.point_list.iter().map(&remap).collect(),
+        ..feedback.clone()
+      })
+      .collect();
 
-  #[cfg_attr(feature = "serde", serde(deserialize_with = "serde_handy::de::to_f64"))]
-  x: f64,
+    self.clone().with_feedback(feedback)
+  }
 
-  #[cfg_attr(feature = "serde", serde(deserialize_with = "serde_handy::de::to_f64"))]
-  y: f64,
+  /// Flips the `x` of every point, points placed by their `index` are moved by `mirrored_index`.
+  pub fn flipped(&self, mirrored_index: impl Fn(u32) -> u32) -> Self {
+    self.remapped(|point| point.flipped(&mirrored_index))
+  }
 }
 
 #[derive(Derivative)]
@@ -0,0 +1,183 @@
+use derivative::Derivative;
+use getset::{Getters, WithSetters};
+
+#[cfg(feature = "serde")]
+use crate::ExtraFields;
+use crate::{DevicePosition, Layout, LayoutPoint};
+
+/// Point of either feedback mode. The clients send the same shape for both of them: dot points
+/// reference a motor by `index`, path points place it by `x`/`y` at a `time`, but Designer
+/// exports mix the forms, so every field, except for the `intensity`, is optional and whatever
+/// is present is kept.
+#[derive(Derivative, Getters, WithSetters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[getset(get = "pub", set_with = "pub")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct EffectPoint {
+  /// reference to the `index` field of the [crate::LayoutPoint] in the [crate::Layout]
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  index: Option<u32>,
+
+  #[cfg_attr(feature = "serde", serde(deserialize_with = "serde_handy::de::to_f64"))]
+  intensity: f64,
+
+  /// Milliseconds from the start of the effect, only the path mode uses it.
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  time: Option<u32>,
+
+  #[cfg_attr(
+    feature = "serde",
+    serde(
+      default,
+      deserialize_with = "serde_handy::de::to_opt_f64",
+      skip_serializing_if = "Option::is_none"
+    )
+  )]
+  x: Option<f64>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(
+      default,
+      deserialize_with = "serde_handy::de::to_opt_f64",
+      skip_serializing_if = "Option::is_none"
+    )
+  )]
+  y: Option<f64>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
+}
+
+impl EffectPoint {
+  /// Index based point, as in the dot mode.
+  pub fn dot(index: u32, intensity: f64) -> Self {
+    Self {
+      index: Some(index),
+      intensity,
+      time: None,
+      x: None,
+      y: None,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  /// Coordinate based point, as in the path mode.
+  pub fn path(x: f64, y: f64, time: u32, intensity: f64) -> Self {
+    Self {
+      index: None,
+      intensity,
+      time: Some(time),
+      x: Some(x),
+      y: Some(y),
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  pub fn time_millis(&self) -> u32 {
+    self.time.unwrap_or(0)
+  }
+
+  /// Motor of the point: its `index`, or the motor of the position nearest to its `x`/`y`.
+  pub fn index_in(&self, layout: &Layout, position: &DevicePosition) -> Option<u32> {
+    if let Some(index) = self.index {
+      return Some(index);
+    }
+
+    let (x, y) = self.x.zip(self.y)?;
+    layout
+      .points(position)?
+      .iter()
+      .min_by(|a, b| distance(a, x, y).total_cmp(&distance(b, x, y)))
+      .map(|point| *point.index())
+  }
+
+  /// Coordinates of the point: its `x`/`y`, or those of the motor with its `index`.
+  pub fn coordinates_in(&self, layout: &Layout, position: &DevicePosition) -> Option<(f64, f64)> {
+    if let Some(coordinates) = self.x.zip(self.y) {
+      return Some(coordinates);
+    }
+
+    let index = self.index?;
+    layout
+      .points(position)?
+      .iter()
+      .find(|point| *point.index() == index)
+      .map(|point| (*point.x(), *point.y()))
+  }
+
+  /// Index based copy of the point, `None` if it cannot be placed onto the layout.
+  pub fn to_dot(&self, layout: &Layout, position: &DevicePosition) -> Option<Self> {
+    Some(Self::dot(self.index_in(layout, position)?, self.intensity))
+  }
+
+  /// Coordinate based copy of the point, `None` if its motor is not in the layout.
+  pub fn to_path(&self, layout: &Layout, position: &DevicePosition) -> Option<Self> {
+    let (x, y) = self.coordinates_in(layout, position)?;
+    Some(Self::path(x, y, self.time_millis(), self.intensity))
+  }
+
+  /// Copy of the point placed by the coordinates of its motor instead of its `index`, points
+  /// with coordinates and of the motors missing in the layout are kept as they are.
+  pub(crate) fn with_coordinates_in(&self, layout: &Layout, position: &DevicePosition) -> Self {
+    if self.x.is_some() && self.y.is_some() {
+      return self.clone();
+    }
+
+    match self.coordinates_in(layout, position) {
+      Some((x, y)) => Self {
+        index: None,
+        x: Some(x),
+        y: Some(y),
+        ..self.clone()
+      },
+      None => self.clone(),
+    }
+  }
+
+  /// Mirrors the point horizontally, the `index` is moved by `mirrored_index`.
+  pub(crate) fn flipped(&self, mirrored_index: impl Fn(u32) -> u32) -> Self {
+    Self {
+      index: self.index.map(mirrored_index),
+      x: self.x.map(|x| 1.0 - x),
+      ..self.clone()
+    }
+  }
+}
+
+fn distance(point: &LayoutPoint, x: f64, y: f64) -> f64 {
+  (point.x() - x).hypot(point.y() - y)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::DeviceType;
+
+  #[test]
+  fn points_convert_through_the_layout() {
+    let layout = DeviceType::TactSuitX40.layout();
+    let position = DevicePosition::VestFront;
+
+    let dot = EffectPoint::dot(6, 0.5);
+    let path = dot.to_path(&layout, &position).unwrap();
+    assert_eq!(path, EffectPoint::path(0.667, 0.25, 0, 0.5));
+    assert_eq!(path.to_dot(&layout, &position), Some(dot));
+
+    // in between the motors the nearest one is used
+    let between = EffectPoint::path(0.6, 0.3, 100, 1.0);
+    assert_eq!(between.index_in(&layout, &position), Some(6));
+
+    assert_eq!(EffectPoint::dot(40, 1.0).to_path(&layout, &position), None);
+  }
+}
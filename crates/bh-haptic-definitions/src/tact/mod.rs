@@ -1,3 +1,4 @@
+mod compose;
 mod effect;
 mod frame;
 mod track;
@@ -8,16 +9,25 @@ pub use track::*;
 
 use derivative::Derivative;
 use derive_more::with_trait::Display;
-use getset::Getters;
+use getset::{Getters, WithSetters};
 use std::collections::HashMap;
 
+use crate::{DevicePosition, RetargetMethod};
+
+/// Fields of a JSON object, which are not modelled by this crate. They are kept, so writing
+/// a parsed file back does not lose them.
+#[cfg(feature = "serde")]
+pub type ExtraFields = serde_json::Map<String, serde_json::Value>;
+
 #[derive(Derivative, Getters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
 #[get = "pub"]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HapticDefinitionTactFilePattern {
-  position: String,
+  #[getset(skip)]
+  #[cfg_attr(feature = "serde", serde(with = "pattern_position"))]
+  position: PatternPosition,
   tact_file: TactFileProject,
 }
 
@@ -30,23 +40,69 @@ pub struct HapticDefinitionTactFilePattern {
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct TactFile {
   project: TactFileProject,
+
+  /// Leftovers of the frame based format, the Designer still writes them.
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  duration_millis: Option<u32>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  interval_millis: Option<u32>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  size: Option<u32>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
-#[derive(Derivative, Getters)]
+/// Id of a project, the Designer writes strings, but older files have numbers. It is written
+/// back as it was read.
+#[derive(Derivative, Display)]
+#[derivative(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum ProjectId {
+  #[display("{_0}")]
+  Number(i64),
+  #[display("{_0}")]
+  Text(String),
+}
+
+impl From<String> for ProjectId {
+  fn from(id: String) -> Self {
+    ProjectId::Text(id)
+  }
+}
+
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct TactFileProject {
   #[cfg_attr(
     feature = "serde",
-    serde(
-      default,
-      deserialize_with = "serde_handy::de::from_str_num_to_opt_string"
-    )
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  id: Option<ProjectId>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
   )]
-  id: Option<String>,
   name: Option<String>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   description: Option<String>,
 
   #[cfg_attr(feature = "serde", serde(default, alias = "Tracks"))]
@@ -55,15 +111,33 @@ pub struct TactFileProject {
   #[cfg_attr(feature = "serde", serde(alias = "Layout"))]
   layout: Layout,
 
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   media_file_duration: Option<f64>,
 
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   created_at: Option<u64>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   updated_at: Option<u64>,
+
+  /// `category`, `tags`, `media` and anything else the Designer adds.
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
-#[derive(Derivative, Getters)]
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Layout {
@@ -72,12 +146,21 @@ pub struct Layout {
   r#type: String,
 
   /// List of points to reference in tracks.
-  layouts: Option<HashMap<String, Vec<LayoutPoint>>>,
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  layouts: Option<HashMap<DevicePosition, Vec<LayoutPoint>>>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
 }
 
-#[derive(Derivative, Getters)]
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct LayoutPoint {
@@ -89,3 +172,279 @@ pub struct LayoutPoint {
   #[cfg_attr(feature = "serde", serde(deserialize_with = "serde_handy::de::to_f64"))]
   y: f64,
 }
+
+impl HapticDefinitionTactFilePattern {
+  pub fn new(position: DevicePosition, tact_file: TactFileProject) -> Self {
+    Self {
+      position: position.into(),
+      tact_file,
+    }
+  }
+
+  pub fn position(&self) -> &DevicePosition {
+    &self.position.position
+  }
+}
+
+impl LayoutPoint {
+  pub fn new(index: u32, x: f64, y: f64) -> Self {
+    Self { index, x, y }
+  }
+}
+
+impl TactFile {
+  /// Wraps the project with the frame leftovers set as the Designer does.
+  pub fn new(project: TactFileProject) -> Self {
+    Self {
+      project,
+      duration_millis: Some(0),
+      interval_millis: Some(20),
+      size: Some(20),
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  #[cfg(feature = "serde")]
+  pub fn extra(&self) -> &ExtraFields {
+    &self.extra
+  }
+
+  #[cfg(feature = "serde")]
+  pub fn from_json(json: &str) -> serde_json::Result<Self> {
+    serde_json::from_str(json)
+  }
+
+  /// Writes the file the way the Designer does: compact JSON with sorted keys, both effect
+  /// modes and all the fields, which were present when the file was parsed.
+  #[cfg(feature = "serde")]
+  pub fn to_json(&self) -> serde_json::Result<String> {
+    // going through `Value` sorts the keys, including those of the `HashMap`s
+    serde_json::to_string(&serde_json::to_value(self)?)
+  }
+
+  /// See [TactFile::to_json].
+  #[cfg(feature = "serde")]
+  pub fn write_json<W: std::io::Write>(&self, writer: W) -> serde_json::Result<()> {
+    serde_json::to_writer(writer, &serde_json::to_value(self)?)
+  }
+}
+
+impl TactFileProject {
+  pub fn new(layout: Layout) -> Self {
+    Self {
+      id: None,
+      name: None,
+      description: None,
+      tracks: vec![],
+      layout,
+      media_file_duration: None,
+      created_at: None,
+      updated_at: None,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  /// Appends the track.
+  pub fn with_track(mut self, track: Track) -> Self {
+    self.tracks.push(track);
+    self
+  }
+
+  #[cfg(feature = "serde")]
+  pub fn extra(&self) -> &ExtraFields {
+    &self.extra
+  }
+
+  /// Positions with at least one effect mode.
+  pub fn positions(&self) -> Vec<DevicePosition> {
+    let mut positions = Vec::<DevicePosition>::new();
+    for effect in self.tracks.iter().flat_map(|track| track.effects()) {
+      for position in effect.modes().keys() {
+        if !positions.contains(position) {
+          positions.push(position.clone());
+        }
+      }
+    }
+    positions
+  }
+
+  /// Moves the patterns onto the motors of the `target` layout, e.g. of a
+  /// [crate::DeviceType::TactSuitX16], see [crate::HapticTimeline::retargeted].
+  ///
+  /// Positions missing in the project layout are taken from the catalogue, positions missing in
+  /// the `target` keep their layout.
+  pub fn retargeted(&self, target: &Layout, method: RetargetMethod) -> Self {
+    let source = self.layout.with_default_points(self.positions());
+
+    let mut layouts = source.layouts.clone().unwrap_or_default();
+    layouts.extend(target.layouts.clone().unwrap_or_default());
+    let layout = source
+      .clone()
+      .with_name(target.name.clone())
+      .with_type(target.r#type.clone())
+      .with_layouts(Some(layouts));
+
+    Self {
+      tracks: self
+        .tracks
+        .iter()
+        .map(|track| track.retargeted(&source, target, method))
+        .collect(),
+      layout,
+      ..self.clone()
+    }
+  }
+
+  /// Mirrors the project across the body midline, see [HapticEffect::mirrored].
+  ///
+  /// Positions missing in the project layout are mirrored with the catalogue layouts.
+  pub fn mirrored(&self) -> Self {
+    let layout = self.layout.with_default_points(self.positions());
+
+    Self {
+      tracks: self
+        .tracks
+        .iter()
+        .map(|track| track.mirrored(&layout))
+        .collect(),
+      layout: self.layout.mirrored(),
+      ..self.clone()
+    }
+  }
+}
+
+impl Layout {
+  pub fn new(
+    name: String,
+    r#type: String,
+    layouts: Option<HashMap<DevicePosition, Vec<LayoutPoint>>>,
+  ) -> Self {
+    Self {
+      name,
+      r#type,
+      layouts,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  #[cfg(feature = "serde")]
+  pub fn extra(&self) -> &ExtraFields {
+    &self.extra
+  }
+
+  /// Swaps the points of the left and right positions.
+  pub fn mirrored(&self) -> Self {
+    let layouts = self.layouts.as_ref().map(|layouts| {
+      layouts
+        .iter()
+        .map(|(position, points)| (position.mirrored(), points.clone()))
+        .collect()
+    });
+
+    Self {
+      layouts,
+      ..self.clone()
+    }
+  }
+
+  /// Index of the motor at the other side of the layout, i.e. with the `x` flipped.
+  pub fn mirrored_index(&self, position: &DevicePosition, index: u32) -> u32 {
+    let Some(points) = self.points(position) else {
+      return index;
+    };
+    let Some(point) = points.iter().find(|point| point.index == index) else {
+      return index;
+    };
+
+    let (x, y) = (1.0 - point.x, point.y);
+    points
+      .iter()
+      .min_by(|a, b| {
+        let a = (a.x - x).hypot(a.y - y);
+        let b = (b.x - x).hypot(b.y - y);
+        a.total_cmp(&b)
+      })
+      .map(|point| point.index)
+      .unwrap_or(index)
+  }
+}
+
+/// Position of a definitions pattern along with the name it was read from, so that writing the
+/// pattern back keeps its spelling.
+#[derive(Derivative)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PatternPosition {
+  pub(crate) position: DevicePosition,
+  #[derivative(Debug = "ignore", PartialEq = "ignore")]
+  name: Option<String>,
+}
+
+impl From<DevicePosition> for PatternPosition {
+  fn from(position: DevicePosition) -> Self {
+    Self {
+      position,
+      name: None,
+    }
+  }
+}
+
+/// The haptic definitions name the position of a pattern after the device it was designed for,
+/// e.g. `LeftArm` holds both the `ForearmL` and `ForearmR` modes. Other names are parsed as
+/// the mode keys are. Positions are written with the name they were read from, legacy names are
+/// used for the ones built in code.
+#[cfg(feature = "serde")]
+pub(crate) mod pattern_position {
+  use serde::{Deserialize, Deserializer, Serializer};
+
+  use super::PatternPosition;
+  use crate::DevicePosition;
+
+  const NAMES: [(&str, DevicePosition); 8] = [
+    ("Vest", DevicePosition::Vest),
+    ("Face", DevicePosition::Head),
+    ("LeftArm", DevicePosition::ForearmL),
+    ("RightArm", DevicePosition::ForearmR),
+    ("LeftHand", DevicePosition::HandL),
+    ("RightHand", DevicePosition::HandR),
+    ("LeftFoot", DevicePosition::FootL),
+    ("RightFoot", DevicePosition::FootR),
+  ];
+
+  pub fn serialize<S>(position: &PatternPosition, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    if let Some(name) = &position.name {
+      return serializer.serialize_str(name);
+    }
+    match NAMES.iter().find(|(_, known)| *known == position.position) {
+      Some((name, _)) => serializer.serialize_str(name),
+      None => serializer.collect_str(&position.position),
+    }
+  }
+
+  pub fn deserialize<'de, D>(deserializer: D) -> Result<PatternPosition, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    let name = String::deserialize(deserializer)?;
+    Ok(PatternPosition {
+      position: from_name(&name).unwrap_or_else(|| DevicePosition::Unknown(name.clone())),
+      name: Some(name),
+    })
+  }
+
+  /// Position of the name, `None` if it is not known.
+  pub fn from_name(name: &str) -> Option<DevicePosition> {
+    match NAMES.iter().find(|(known, _)| *known == name) {
+      Some((_, position)) => Some(position.clone()),
+      None => name
+        .parse()
+        .ok()
+        .filter(|position: &DevicePosition| !position.is_unknown()),
+    }
+  }
+}
@@ -1,16 +1,73 @@
 use derivative::Derivative;
-use getset::Getters;
+use getset::{Getters, WithSetters};
 
-use crate::HapticEffect;
+#[cfg(feature = "serde")]
+use crate::ExtraFields;
+use crate::{HapticEffect, Layout, RetargetMethod};
 
-#[derive(Derivative, Getters)]
+#[derive(Derivative, Getters, WithSetters)]
 #[derivative(Debug, Clone, PartialEq, Eq)]
-#[get = "pub"]
+#[getset(get = "pub", set_with = "pub")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Track {
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
   enable: Option<bool>,
 
   #[cfg_attr(feature = "serde", serde(default))]
   effects: Vec<HapticEffect>,
+
+  #[cfg(feature = "serde")]
+  #[getset(skip)]
+  #[serde(flatten)]
+  extra: ExtraFields,
+}
+
+impl Track {
+  /// Enabled track with the effects.
+  pub fn new(effects: Vec<HapticEffect>) -> Self {
+    Self {
+      enable: Some(true),
+      effects,
+      #[cfg(feature = "serde")]
+      extra: ExtraFields::new(),
+    }
+  }
+
+  /// Appends the effect.
+  pub fn with_effect(mut self, effect: HapticEffect) -> Self {
+    self.effects.push(effect);
+    self
+  }
+
+  pub fn mirrored(&self, layout: &Layout) -> Self {
+    Self {
+      effects: self
+        .effects
+        .iter()
+        .map(|effect| effect.mirrored(layout))
+        .collect(),
+      ..self.clone()
+    }
+  }
+
+  pub fn retargeted(&self, source: &Layout, target: &Layout, method: RetargetMethod) -> Self {
+    Self {
+      effects: self
+        .effects
+        .iter()
+        .map(|effect| effect.retargeted(source, target, method))
+        .collect(),
+      ..self.clone()
+    }
+  }
+
+  /// Fields of the track, which are not modelled by this crate.
+  #[cfg(feature = "serde")]
+  pub fn extra(&self) -> &ExtraFields {
+    &self.extra
+  }
 }
@@ -0,0 +1,27 @@
+use crate::{HapticTimeline, Layout, PositionTimeline};
+
+impl HapticTimeline {
+  /// Mirrors the timeline across the body midline, the same way [crate::TactFileProject::mirrored]
+  /// does: left and right positions are swapped, midline positions are flipped within `layout`.
+  pub fn mirrored(&self, layout: &Layout) -> HapticTimeline {
+    let mut result = HapticTimeline::new(*self.tick_millis(), *self.duration_millis());
+
+    for (position, timeline) in self.positions() {
+      if position.is_left() || position.is_right() {
+        result.set_position(position.mirrored(), timeline.clone());
+        continue;
+      }
+
+      let mut flipped = PositionTimeline::new(*timeline.motor_count(), timeline.frames().len());
+      for (tick, frame) in timeline.frames().iter().enumerate() {
+        for (motor, intensity) in frame.iter().enumerate() {
+          let mirrored = layout.mirrored_index(position, motor as u32) as usize;
+          flipped.set_intensity(tick, mirrored, *intensity);
+        }
+      }
+      result.set_position(position.clone(), flipped);
+    }
+
+    result
+  }
+}
@@ -0,0 +1,9 @@
+//! Transformations of rendered [crate::HapticTimeline]s, applied right before playback.
+
+mod mirror;
+mod retarget;
+mod rotate;
+mod scale;
+
+pub use retarget::*;
+pub use scale::*;
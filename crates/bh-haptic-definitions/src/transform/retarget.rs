@@ -0,0 +1,282 @@
+use derivative::Derivative;
+use std::collections::HashMap;
+
+use crate::{DevicePosition, HapticTimeline, Layout, LayoutPoint, PositionTimeline};
+
+/// How the motors of one layout are mapped onto the motors of another one.
+#[derive(Derivative)]
+#[derivative(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum RetargetMethod {
+  /// Every target motor plays its nearest source motor, and every source motor is played by its
+  /// nearest target motor, so no motor gets lost when the target has fewer of them. Overlapping
+  /// motors keep the strongest intensity.
+  #[derivative(Default)]
+  NearestNeighbour,
+
+  /// Every target motor plays the average of the source motors its area overlaps, weighted by
+  /// the overlap. The area of a motor is the cell around it, as wide as the motor spacing.
+  AreaWeighted,
+}
+
+/// Mapping between the motors of a single position of two layouts.
+#[derive(Derivative)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct MotorMapping {
+  method: RetargetMethod,
+
+  /// Source motors with their weight, for every target motor index.
+  weights: Vec<(usize, Vec<(usize, f64)>)>,
+}
+
+impl MotorMapping {
+  pub fn new(source: &[LayoutPoint], target: &[LayoutPoint], method: RetargetMethod) -> Self {
+    let weights = match method {
+      RetargetMethod::NearestNeighbour => nearest_weights(source, target),
+      RetargetMethod::AreaWeighted => area_weights(source, target),
+    };
+
+    Self { method, weights }
+  }
+
+  /// Maps `(motor, intensity)` pairs of the source onto the target motors, silent target motors
+  /// are left out.
+  pub fn map(&self, source: &[(usize, f64)]) -> Vec<(usize, f64)> {
+    let intensities = source.iter().copied().collect::<HashMap<_, _>>();
+
+    self
+      .weights
+      .iter()
+      .filter_map(|(target, weights)| {
+        let contributions = weights
+          .iter()
+          .map(|(source, weight)| intensities.get(source).copied().unwrap_or(0.0) * weight);
+        let intensity = match self.method {
+          RetargetMethod::NearestNeighbour => contributions.fold(0.0, f64::max),
+          RetargetMethod::AreaWeighted => contributions.sum(),
+        };
+
+        (intensity > 0.0).then_some((*target, intensity.clamp(0.0, 1.0)))
+      })
+      .collect()
+  }
+}
+
+impl HapticTimeline {
+  /// Remaps the motors of every position from the `source` layout onto the `target` one, e.g. to
+  /// play an X40 pattern on an X16.
+  ///
+  /// Positions without points in either layout are left as they are.
+  pub fn retargeted(
+    &self,
+    source: &Layout,
+    target: &Layout,
+    method: RetargetMethod,
+  ) -> HapticTimeline {
+    let mut result = self.clone();
+
+    for (position, timeline) in self.positions() {
+      let Some(mapping) = position_mapping(source, target, position, method) else {
+        continue;
+      };
+
+      let mut retargeted = PositionTimeline::new(
+        target.motor_count(position).unwrap_or(0),
+        timeline.frames().len(),
+      );
+      for (tick, frame) in timeline.frames().iter().enumerate() {
+        let motors = frame.iter().copied().enumerate().collect::<Vec<_>>();
+        for (motor, intensity) in mapping.map(&motors) {
+          retargeted.set_intensity(tick, motor, intensity);
+        }
+      }
+      result.set_position(position.clone(), retargeted);
+    }
+
+    result
+  }
+}
+
+/// Mapping of the position between the layouts, `None` if either of them has no points for it.
+pub(crate) fn position_mapping(
+  source: &Layout,
+  target: &Layout,
+  position: &DevicePosition,
+  method: RetargetMethod,
+) -> Option<MotorMapping> {
+  let source = source.points(position)?;
+  let target = target.points(position)?;
+
+  Some(MotorMapping::new(source, target, method))
+}
+
+fn nearest_weights(
+  source: &[LayoutPoint],
+  target: &[LayoutPoint],
+) -> Vec<(usize, Vec<(usize, f64)>)> {
+  let mut weights = target
+    .iter()
+    .map(|point| {
+      let nearest = nearest(source, point).map(|index| (index, 1.0));
+      (
+        *point.index() as usize,
+        nearest.into_iter().collect::<Vec<_>>(),
+      )
+    })
+    .collect::<Vec<_>>();
+
+  for point in source {
+    let Some(nearest_target) = nearest(target, point) else {
+      continue;
+    };
+    let source_index = *point.index() as usize;
+
+    if let Some((_, sources)) = weights
+      .iter_mut()
+      .find(|(index, _)| *index == nearest_target)
+      && !sources.iter().any(|(index, _)| *index == source_index)
+    {
+      sources.push((source_index, 1.0));
+    }
+  }
+
+  weights
+}
+
+fn nearest(points: &[LayoutPoint], to: &LayoutPoint) -> Option<usize> {
+  points
+    .iter()
+    .min_by(|a, b| distance(a, to).total_cmp(&distance(b, to)))
+    .map(|point| *point.index() as usize)
+}
+
+fn distance(a: &LayoutPoint, b: &LayoutPoint) -> f64 {
+  (a.x() - b.x()).hypot(a.y() - b.y())
+}
+
+fn area_weights(source: &[LayoutPoint], target: &[LayoutPoint]) -> Vec<(usize, Vec<(usize, f64)>)> {
+  let source_cells = cells(source);
+
+  cells(target)
+    .into_iter()
+    .map(|(target_index, target_cell)| {
+      let overlaps = source_cells
+        .iter()
+        .map(|(source_index, source_cell)| (*source_index, target_cell.overlap(source_cell)))
+        // touching cells may still overlap by a rounding error
+        .filter(|(_, overlap)| *overlap > 1e-9)
+        .collect::<Vec<_>>();
+
+      // only the covered part counts, so a uniform pattern stays uniform at the edges
+      let covered = overlaps.iter().map(|(_, overlap)| overlap).sum::<f64>();
+      let weights = overlaps
+        .into_iter()
+        .map(|(index, overlap)| (index, overlap / covered))
+        .collect();
+
+      (target_index, weights)
+    })
+    .collect()
+}
+
+/// Area of a single motor.
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+  x: (f64, f64),
+  y: (f64, f64),
+}
+
+impl Cell {
+  fn overlap(&self, other: &Cell) -> f64 {
+    let overlap = |a: (f64, f64), b: (f64, f64)| (a.1.min(b.1) - a.0.max(b.0)).max(0.0);
+    overlap(self.x, other.x) * overlap(self.y, other.y)
+  }
+}
+
+fn cells(points: &[LayoutPoint]) -> Vec<(usize, Cell)> {
+  let width = spacing(points.iter().map(|point| *point.x()));
+  let height = spacing(points.iter().map(|point| *point.y()));
+
+  points
+    .iter()
+    .map(|point| {
+      let cell = Cell {
+        x: (point.x() - width / 2.0, point.x() + width / 2.0),
+        y: (point.y() - height / 2.0, point.y() + height / 2.0),
+      };
+      (*point.index() as usize, cell)
+    })
+    .collect()
+}
+
+/// Smallest gap between the distinct coordinates, a single row or column spans the whole layout.
+fn spacing(values: impl Iterator<Item = f64>) -> f64 {
+  let mut values = values.collect::<Vec<_>>();
+  values.sort_by(f64::total_cmp);
+  values.dedup_by(|a, b| (*a - *b).abs() < 0.01);
+
+  values
+    .windows(2)
+    .map(|pair| pair[1] - pair[0])
+    .min_by(f64::total_cmp)
+    .unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::DeviceType;
+
+  fn vest_points(device_type: DeviceType) -> Vec<LayoutPoint> {
+    device_type
+      .layout_points(&DevicePosition::VestFront)
+      .unwrap()
+  }
+
+  #[test]
+  fn same_layout_is_identity() {
+    let points = vest_points(DeviceType::TactSuitX40);
+    let source = vec![(0, 0.5), (7, 1.0), (19, 0.25)];
+
+    for method in [
+      RetargetMethod::NearestNeighbour,
+      RetargetMethod::AreaWeighted,
+    ] {
+      let mapping = MotorMapping::new(&points, &points, method);
+      assert_eq!(mapping.map(&source), source, "{method:?}");
+    }
+  }
+
+  #[test]
+  fn nearest_neighbour_keeps_every_motor() {
+    let x40 = vest_points(DeviceType::TactSuitX40);
+    let x16 = vest_points(DeviceType::TactSuitX16);
+
+    // the top left corner of the X40 ends up in the top left corner of the X16
+    let down = MotorMapping::new(&x40, &x16, RetargetMethod::NearestNeighbour);
+    assert_eq!(down.map(&[(1, 0.5)]), vec![(0, 0.5)]);
+    assert_eq!(down.map(&[(19, 1.0)]), vec![(7, 1.0)]);
+
+    let up = MotorMapping::new(&x16, &x40, RetargetMethod::NearestNeighbour);
+    let all = (0..8).map(|motor| (motor, 1.0)).collect::<Vec<_>>();
+    assert_eq!(up.map(&all).len(), 20);
+  }
+
+  #[test]
+  fn area_weighted_averages_covered_motors() {
+    let x40 = vest_points(DeviceType::TactSuitX40);
+    let x16 = vest_points(DeviceType::TactSuitX16);
+    let mapping = MotorMapping::new(&x40, &x16, RetargetMethod::AreaWeighted);
+
+    let all = (0..20).map(|motor| (motor, 1.0)).collect::<Vec<_>>();
+    for (_, intensity) in mapping.map(&all) {
+      assert!((intensity - 1.0).abs() < 1e-9);
+    }
+
+    let corner = mapping.map(&[(0, 1.0)]);
+    assert_eq!(corner.len(), 1);
+    assert_eq!(corner[0].0, 0);
+    assert!(corner[0].1 > 0.0 && corner[0].1 < 1.0);
+  }
+}
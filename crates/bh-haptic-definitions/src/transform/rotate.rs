@@ -0,0 +1,126 @@
+use tracing::*;
+
+use crate::{DevicePosition, HapticTimeline, Layout, LayoutPoint, PositionTimeline};
+
+/// Degrees of the torso covered by a single side of the vest.
+const SIDE_DEGREES: f64 = 180.0;
+
+/// Motor position on the cylinder formed by the `VestFront` and `VestBack` layouts.
+///
+/// The front covers -90° to 90°, the back continues from 90° to 270°, so the right edge of the
+/// front meets the left edge of the back and the other way around. Edge columns are inset by
+/// half of the column spacing, so the seams are as wide as the gaps between columns.
+#[derive(Debug, Clone, Copy)]
+struct CylinderPoint {
+  angle: f64,
+  y: f64,
+}
+
+impl CylinderPoint {
+  fn new(position: &DevicePosition, point: &LayoutPoint, column_spacing: f64) -> Self {
+    let start = match position {
+      DevicePosition::VestBack => SIDE_DEGREES / 2.0,
+      _ => -SIDE_DEGREES / 2.0,
+    };
+    let x = (point.x() + column_spacing / 2.0) / (1.0 + column_spacing);
+
+    Self {
+      angle: start + x * SIDE_DEGREES,
+      y: *point.y(),
+    }
+  }
+
+  /// Distance in layout units, one side of the vest being `1.0` wide.
+  fn distance(&self, other: &CylinderPoint) -> f64 {
+    let angle = ((self.angle - other.angle + 180.0).rem_euclid(360.0) - 180.0).abs();
+    (angle / SIDE_DEGREES).hypot(self.y - other.y)
+  }
+}
+
+impl HapticTimeline {
+  /// Rotates the vest pattern around the torso and moves it vertically.
+  ///
+  /// A positive `angle_x` (degrees) moves the front pattern towards its higher `x` and further
+  /// onto the back. A positive `offset_y` moves the pattern up, towards the lower layout `y`.
+  /// Every motor takes the intensity of the motor nearest to where it is rotated from, motors
+  /// rotated from above or below the vest stay silent.
+  pub fn rotated(&self, layout: &Layout, angle_x: f64, offset_y: f64) -> HapticTimeline {
+    if self.position(&DevicePosition::VestFront).is_none()
+      && self.position(&DevicePosition::VestBack).is_none()
+    {
+      return self.clone();
+    }
+
+    let front = layout.points(&DevicePosition::VestFront);
+    let back = layout.points(&DevicePosition::VestBack);
+    let (Some(front), Some(back)) = (front, back) else {
+      warn!("Layout {} has no vest front and back points", layout.name());
+      return self.clone();
+    };
+
+    let motors = [
+      (DevicePosition::VestFront, front),
+      (DevicePosition::VestBack, back),
+    ]
+    .into_iter()
+    .flat_map(|(position, points)| {
+      let spacing = column_spacing(points);
+      points.iter().map(move |point| {
+        let cylinder_point = CylinderPoint::new(&position, point, spacing);
+        (position.clone(), point, cylinder_point)
+      })
+    })
+    .collect::<Vec<_>>();
+
+    let mut result = self.clone();
+    let mut sides = [DevicePosition::VestFront, DevicePosition::VestBack].map(|position| {
+      let motor_count = layout.motor_count(&position).unwrap_or(0);
+      PositionTimeline::new(motor_count, self.frame_count())
+    });
+
+    for (position, point, target) in &motors {
+      let source = CylinderPoint {
+        angle: target.angle - angle_x,
+        y: target.y + offset_y,
+      };
+      if !(-f64::EPSILON..=1.0 + f64::EPSILON).contains(&source.y) {
+        continue;
+      }
+
+      let Some((source_position, source_point, _)) = motors
+        .iter()
+        .min_by(|a, b| source.distance(&a.2).total_cmp(&source.distance(&b.2)))
+      else {
+        continue;
+      };
+      let Some(source_timeline) = self.position(source_position) else {
+        continue;
+      };
+
+      let side = usize::from(*position == DevicePosition::VestBack);
+      for tick in 0..self.frame_count() {
+        let intensity = source_timeline.intensity(tick, *source_point.index() as usize);
+        sides[side].set_intensity(tick, *point.index() as usize, intensity);
+      }
+    }
+
+    let [front, back] = sides;
+    result.set_position(DevicePosition::VestFront, front);
+    result.set_position(DevicePosition::VestBack, back);
+
+    result
+  }
+}
+
+/// Smallest distance between two columns of the layout, `1.0` if there is a single column.
+fn column_spacing(points: &[LayoutPoint]) -> f64 {
+  let mut columns = points.iter().map(|point| *point.x()).collect::<Vec<_>>();
+  columns.sort_by(f64::total_cmp);
+
+  columns
+    .windows(2)
+    .map(|pair| pair[1] - pair[0])
+    .filter(|spacing| *spacing > f64::EPSILON)
+    .min_by(f64::total_cmp)
+    .unwrap_or(1.0)
+}
@@ -0,0 +1,141 @@
+use anyhow::ensure;
+
+use crate::{HapticDefinitionMapping, HapticTimeline};
+
+/// Highest duration scale of a play request, longer ones are played this long.
+pub const MAX_DURATION_SCALE: f64 = 100.0;
+
+/// Duration scale of a play request capped at [MAX_DURATION_SCALE], negative and non-finite
+/// ones are rejected.
+pub fn capped_duration_scale(scale: f64) -> anyhow::Result<f64> {
+  ensure!(
+    scale.is_finite() && scale >= 0.0,
+    "Invalid duration scale {scale}"
+  );
+  Ok(scale.min(MAX_DURATION_SCALE))
+}
+
+impl HapticDefinitionMapping {
+  /// Intensity multiplier of the mapping, the `intensity` field is a percentage.
+  pub fn intensity_scale(&self) -> f64 {
+    self
+      .intensity()
+      .map(|intensity| intensity / 100.0)
+      .unwrap_or(1.0)
+  }
+}
+
+impl HapticTimeline {
+  /// Applies the scale factors of a play request on top of the mapping intensity multiplier.
+  pub fn scaled_for(
+    &self,
+    mapping: &HapticDefinitionMapping,
+    intensity: f64,
+    duration: f64,
+  ) -> anyhow::Result<HapticTimeline> {
+    Ok(
+      self
+        .scaled_duration(duration)?
+        .scaled_intensity(intensity * mapping.intensity_scale()),
+    )
+  }
+
+  /// Multiplies every intensity, the result is clamped to `0.0..=1.0`.
+  pub fn scaled_intensity(&self, scale: f64) -> HapticTimeline {
+    let mut result = self.clone();
+
+    for (position, timeline) in self.positions() {
+      let target = result.position_mut(position.clone(), *timeline.motor_count());
+      for (tick, frame) in timeline.frames().iter().enumerate() {
+        for (motor, intensity) in frame.iter().enumerate() {
+          target.set_intensity(tick, motor, intensity * scale);
+        }
+      }
+    }
+
+    result
+  }
+
+  /// Stretches the timeline in time, e.g. `0.5` plays the pattern twice as fast. The scale is
+  /// checked by [capped_duration_scale].
+  pub fn scaled_duration(&self, scale: f64) -> anyhow::Result<HapticTimeline> {
+    let scale = capped_duration_scale(scale)?;
+    let duration_millis = (f64::from(*self.duration_millis()) * scale).round() as u32;
+    let mut result = HapticTimeline::new(*self.tick_millis(), duration_millis);
+    let frame_count = result.frame_count();
+
+    for (position, timeline) in self.positions() {
+      let target = result.position_mut(position.clone(), *timeline.motor_count());
+      for tick in 0..frame_count {
+        // every frame plays the source frame which was due at the same relative time
+        let source_tick = (tick as f64 / scale).floor() as usize;
+        for motor in 0..*timeline.motor_count() {
+          target.set_intensity(tick, motor, timeline.intensity(source_tick, motor));
+        }
+      }
+    }
+
+    Ok(result)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{DevicePosition, HapticTimeline, MAX_DURATION_SCALE};
+
+  fn create_test_timeline() -> HapticTimeline {
+    let mut timeline = HapticTimeline::new(10, 40);
+    let forearm = timeline.position_mut(DevicePosition::ForearmL, 2);
+    for (tick, intensity) in [0.2, 0.4, 0.6, 0.8].into_iter().enumerate() {
+      forearm.set_intensity(tick, 0, intensity);
+    }
+    timeline
+  }
+
+  fn motor_intensities(timeline: &HapticTimeline) -> Vec<f64> {
+    let forearm = timeline.position(&DevicePosition::ForearmL).unwrap();
+    (0..timeline.frame_count())
+      .map(|tick| forearm.intensity(tick, 0))
+      .collect()
+  }
+
+  #[test]
+  fn test_scaled_intensity_clamps() {
+    let scaled = create_test_timeline().scaled_intensity(2.0);
+
+    assert_eq!(motor_intensities(&scaled), vec![0.4, 0.8, 1.0, 1.0]);
+  }
+
+  #[test]
+  fn test_scaled_duration_stretches_and_shrinks() {
+    let timeline = create_test_timeline();
+
+    let stretched = timeline.scaled_duration(2.0).unwrap();
+    assert_eq!(*stretched.duration_millis(), 80);
+    assert_eq!(
+      motor_intensities(&stretched),
+      vec![0.2, 0.2, 0.4, 0.4, 0.6, 0.6, 0.8, 0.8]
+    );
+
+    let shrunk = timeline.scaled_duration(0.5).unwrap();
+    assert_eq!(*shrunk.duration_millis(), 20);
+    assert_eq!(motor_intensities(&shrunk), vec![0.2, 0.6]);
+
+    assert_eq!(timeline.scaled_duration(0.0).unwrap().frame_count(), 0);
+  }
+
+  #[test]
+  fn test_scaled_duration_rejects_invalid_scales_and_caps_long_ones() {
+    let timeline = create_test_timeline();
+
+    for scale in [-1.0, f64::NAN, f64::INFINITY] {
+      assert!(timeline.scaled_duration(scale).is_err(), "{scale}");
+    }
+
+    let capped = timeline.scaled_duration(1e300).unwrap();
+    assert_eq!(
+      *capped.duration_millis(),
+      *timeline.duration_millis() * MAX_DURATION_SCALE as u32
+    );
+  }
+}
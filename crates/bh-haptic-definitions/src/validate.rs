@@ -0,0 +1,267 @@
+use derivative::Derivative;
+use derive_more::with_trait::Display;
+use getset::Getters;
+
+use crate::{
+  DevicePosition, EffectDotMode, EffectMode, EffectPathMode, HapticDefinitionMapping,
+  HapticDefinitionsMessage, HapticEffect, Layout, TactFileProject, default_tick_millis,
+};
+
+#[derive(Derivative, Display)]
+#[derivative(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum DiagnosticSeverity {
+  /// The pattern plays, but probably not the way it was meant to.
+  #[display("warning")]
+  Warning,
+  /// The pattern cannot be played as authored.
+  #[display("error")]
+  Error,
+}
+
+#[derive(Derivative, Display)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "camelCase"))]
+pub enum DiagnosticKind {
+  #[display("dot index {index} is not in the layout of {position}")]
+  UnknownDotIndex {
+    position: DevicePosition,
+    index: u32,
+  },
+
+  #[display("end time {end_time} is before start time {start_time}")]
+  EndBeforeStart { start_time: u32, end_time: u32 },
+
+  #[display("intensity {intensity} is outside of 0..1")]
+  IntensityOutOfRange { intensity: f64 },
+
+  #[display("point list is empty")]
+  EmptyPointList,
+
+  #[display("unknown mode key {key}")]
+  UnknownModeKey { key: String },
+
+  #[display("event time {event_time} does not match the pattern length {pattern_millis}")]
+  EventTimeMismatch {
+    event_time: u32,
+    pattern_millis: u32,
+  },
+}
+
+impl DiagnosticKind {
+  pub fn severity(&self) -> DiagnosticSeverity {
+    match self {
+      DiagnosticKind::UnknownDotIndex { .. }
+      | DiagnosticKind::EndBeforeStart { .. }
+      | DiagnosticKind::IntensityOutOfRange { .. } => DiagnosticSeverity::Error,
+      DiagnosticKind::EmptyPointList
+      | DiagnosticKind::UnknownModeKey { .. }
+      | DiagnosticKind::EventTimeMismatch { .. } => DiagnosticSeverity::Warning,
+    }
+  }
+}
+
+/// Problem found by `validate()`, pointing to the offending JSON value.
+#[derive(Derivative, Getters, Display)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+#[display("{}: {path}: {kind}", kind.severity())]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Diagnostic {
+  /// JSON path relative to the validated value, e.g. `$.tracks[0].effects[1].startTime`.
+  path: String,
+  kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+  pub fn new(path: String, kind: DiagnosticKind) -> Self {
+    Self { path, kind }
+  }
+
+  pub fn severity(&self) -> DiagnosticSeverity {
+    self.kind.severity()
+  }
+
+  pub fn is_error(&self) -> bool {
+    self.severity() == DiagnosticSeverity::Error
+  }
+}
+
+impl HapticDefinitionsMessage {
+  /// Checks every tact pattern of every mapping, see [TactFileProject::validate].
+  pub fn validate(&self) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    for (i, mapping) in self.haptic_mappings().iter().enumerate() {
+      mapping.validate_at(&format!("$.hapticMappings[{i}]"), &mut diagnostics);
+    }
+
+    diagnostics
+  }
+}
+
+impl HapticDefinitionMapping {
+  fn validate_at(&self, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+    for (i, pattern) in self.tact_file_patterns().iter().enumerate() {
+      pattern.tact_file().validate_at(
+        &format!("{path}.tactFilePatterns[{i}].tactFile"),
+        diagnostics,
+      );
+    }
+
+    // mappings without tact patterns are played from their audio clips
+    let Some(pattern_millis) = self
+      .tact_file_patterns()
+      .iter()
+      .map(|pattern| pattern.tact_file().duration_millis())
+      .max()
+    else {
+      return;
+    };
+
+    // the Designer rounds the event time to its ticks
+    if self.event_time().abs_diff(pattern_millis) > default_tick_millis() {
+      diagnostics.push(Diagnostic::new(
+        format!("{path}.eventTime"),
+        DiagnosticKind::EventTimeMismatch {
+          event_time: *self.event_time(),
+          pattern_millis,
+        },
+      ));
+    }
+  }
+}
+
+impl TactFileProject {
+  /// Finds problems, which would make the project render differently than authored.
+  pub fn validate(&self) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    self.validate_at("$", &mut diagnostics);
+    diagnostics
+  }
+
+  fn validate_at(&self, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+    for (i, track) in self.tracks().iter().enumerate() {
+      for (j, effect) in track.effects().iter().enumerate() {
+        effect.validate_at(
+          self.layout(),
+          &format!("{path}.tracks[{i}].effects[{j}]"),
+          diagnostics,
+        );
+      }
+    }
+  }
+}
+
+impl HapticEffect {
+  fn validate_at(&self, layout: &Layout, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let mut modes = self.modes().iter().collect::<Vec<_>>();
+    modes.sort_by_key(|(position, _)| position.to_string());
+
+    for (position, mode) in modes {
+      let path = format!("{path}.modes.{position}");
+      if let DevicePosition::Unknown(key) = position {
+        diagnostics.push(Diagnostic::new(
+          path.clone(),
+          DiagnosticKind::UnknownModeKey { key: key.clone() },
+        ));
+      }
+
+      match mode {
+        EffectMode::DotMode { dot_mode, .. } => validate_dot_mode(
+          dot_mode,
+          layout,
+          position,
+          &format!("{path}.dotMode"),
+          diagnostics,
+        ),
+        EffectMode::PathMode { path_mode, .. } => {
+          validate_path_mode(path_mode, &format!("{path}.pathMode"), diagnostics)
+        }
+      }
+    }
+  }
+}
+
+fn validate_dot_mode(
+  dot_mode: &EffectDotMode,
+  layout: &Layout,
+  position: &DevicePosition,
+  path: &str,
+  diagnostics: &mut Vec<Diagnostic>,
+) {
+  // the tact files of the haptic definitions come without the layout points
+  let points = layout.points(position);
+
+  for (i, feedback) in dot_mode.feedback().iter().enumerate() {
+    let path = format!("{path}.feedback[{i}]");
+
+    if feedback.end_time() < feedback.start_time() {
+      diagnostics.push(Diagnostic::new(
+        format!("{path}.endTime"),
+        DiagnosticKind::EndBeforeStart {
+          start_time: *feedback.start_time(),
+          end_time: *feedback.end_time(),
+        },
+      ));
+    }
+
+    if feedback.point_list().is_empty() {
+      diagnostics.push(Diagnostic::new(
+        format!("{path}.pointList"),
+        DiagnosticKind::EmptyPointList,
+      ));
+    }
+
+    for (j, point) in feedback.point_list().iter().enumerate() {
+      let path = format!("{path}.pointList[{j}]");
+
+      if let Some(points) = points
+        && let Some(index) = point.index()
+        && !points.iter().any(|p| p.index() == index)
+      {
+        diagnostics.push(Diagnostic::new(
+          format!("{path}.index"),
+          DiagnosticKind::UnknownDotIndex {
+            position: position.clone(),
+            index: *index,
+          },
+        ));
+      }
+      validate_intensity(*point.intensity(), &path, diagnostics);
+    }
+  }
+}
+
+fn validate_path_mode(path_mode: &EffectPathMode, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+  for (i, feedback) in path_mode.feedback().iter().enumerate() {
+    let path = format!("{path}.feedback[{i}]");
+
+    if feedback.point_list().is_empty() {
+      diagnostics.push(Diagnostic::new(
+        format!("{path}.pointList"),
+        DiagnosticKind::EmptyPointList,
+      ));
+    }
+
+    for (j, point) in feedback.point_list().iter().enumerate() {
+      validate_intensity(
+        *point.intensity(),
+        &format!("{path}.pointList[{j}]"),
+        diagnostics,
+      );
+    }
+  }
+}
+
+fn validate_intensity(intensity: f64, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+  if !(0.0..=1.0).contains(&intensity) {
+    diagnostics.push(Diagnostic::new(
+      format!("{path}.intensity"),
+      DiagnosticKind::IntensityOutOfRange { intensity },
+    ));
+  }
+}
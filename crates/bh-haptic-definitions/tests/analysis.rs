@@ -0,0 +1,135 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{
+  DevicePosition, HapticDefinitionsMessage, HapticTimeline, RenderOptions, SdkApiResponseV3,
+  TactFile,
+};
+use std::fs::read_to_string;
+
+mod common;
+
+#[test]
+fn timeline_analysis_measures_every_motor() {
+  let mut timeline = HapticTimeline::new(20, 100);
+  let front = timeline.position_mut(DevicePosition::VestFront, 4);
+  front.set_intensity(0, 0, 1.0);
+  front.set_intensity(1, 0, 1.0);
+  front.set_intensity(1, 1, 0.5);
+  let head = timeline.position_mut(DevicePosition::Head, 6);
+  head.set_intensity(1, 2, 0.25);
+  timeline.position_mut(DevicePosition::VestBack, 20);
+
+  let analysis = timeline.analyze();
+
+  assert_eq!(*analysis.duration_millis(), 100);
+  assert_eq!(*analysis.peak_intensity(), 1.0);
+  assert_eq!(*analysis.motors_touched(), 3);
+  assert_eq!(*analysis.max_simultaneous_motors(), 3);
+  assert!(!analysis.is_silent());
+  assert_eq!(
+    analysis
+      .positions()
+      .iter()
+      .map(|position| position.position().clone())
+      .collect::<Vec<_>>(),
+    [
+      DevicePosition::Head,
+      DevicePosition::VestBack,
+      DevicePosition::VestFront
+    ]
+  );
+
+  let [head, back, front] = analysis.positions().as_slice() else {
+    unreachable!()
+  };
+  assert_eq!(*back.motors_touched(), 0);
+  assert_eq!(*back.active_millis(), 0);
+  assert_eq!(*head.active_millis(), 20);
+  assert_eq!(*front.active_millis(), 40);
+  assert_eq!(*front.motors_touched(), 2);
+  assert_eq!(*front.max_simultaneous_motors(), 2);
+  assert_eq!(front.motors().len(), 4);
+
+  let motor = &front.motors()[0];
+  assert_eq!(*motor.index(), 0);
+  assert_eq!(*motor.peak_intensity(), 1.0);
+  assert_eq!(*motor.active_millis(), 40);
+  // 2 of the 5 frames at full intensity
+  assert!((motor.rms_intensity() - (2.0f64 / 5.0).sqrt()).abs() < 1e-9);
+}
+
+#[test]
+fn silent_timeline_analysis() {
+  let mut timeline = HapticTimeline::new(20, 100);
+  timeline.position_mut(DevicePosition::VestFront, 20);
+
+  let analysis = timeline.analyze();
+
+  assert!(analysis.is_silent());
+  assert_eq!(*analysis.peak_intensity(), 0.0);
+  assert_eq!(*analysis.max_simultaneous_motors(), 0);
+  assert_eq!(*analysis.positions()[0].motors()[0].rms_intensity(), 0.0);
+}
+
+#[test]
+fn tact_file_analysis_matches_the_rendered_timeline() -> anyhow::Result<()> {
+  let path = common::fixture_path("tact_file/valid/bonelab/BulletHit.tact");
+  let project = TactFile::from_json(&read_to_string(path)?)?
+    .project()
+    .clone();
+  let options = RenderOptions::default();
+
+  let analysis = project.analyze(&options);
+
+  assert_eq!(analysis, project.render(&options).analyze());
+  assert_eq!(*analysis.duration_millis(), project.duration_millis());
+  assert!(!analysis.is_silent());
+  assert!(*analysis.peak_intensity() <= 1.0);
+  for position in analysis.positions() {
+    assert!(*position.active_millis() <= *analysis.duration_millis());
+    assert!(position.max_simultaneous_motors() <= position.motors_touched());
+  }
+
+  Ok(())
+}
+
+#[test]
+fn haptic_definitions_analysis_is_serializable() -> anyhow::Result<()> {
+  let dir = common::fixture_path("haptic_definitions").join("valid");
+
+  for entry in std::fs::read_dir(dir)? {
+    let path = entry?.path();
+    let response =
+      serde_json::from_str::<SdkApiResponseV3<HapticDefinitionsMessage>>(&read_to_string(&path)?)?;
+    let definitions = response.message().clone().unwrap();
+
+    let analysis = definitions.analyze(&RenderOptions::default());
+    assert_eq!(
+      analysis.mappings().len(),
+      definitions.haptic_mappings().len()
+    );
+    for (mapping, analysis) in definitions
+      .haptic_mappings()
+      .iter()
+      .zip(analysis.mappings())
+    {
+      assert_eq!(mapping.key(), analysis.key());
+      assert_eq!(
+        analysis.pattern().is_silent(),
+        mapping
+          .render(&RenderOptions::default())
+          .positions()
+          .values()
+          .all(|timeline| timeline.is_silent()),
+        "{}",
+        mapping.key()
+      );
+    }
+
+    let json = serde_json::to_value(&analysis)?;
+    assert!(json["mappings"].is_array(), "{}", path.display());
+    assert!(json["tickMillis"].is_u64());
+  }
+
+  Ok(())
+}
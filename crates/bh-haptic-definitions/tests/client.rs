@@ -0,0 +1,193 @@
+#![cfg(feature = "client")]
+
+use bh_haptic_definitions::{HapticDefinitionsClient, HapticDefinitionsError};
+use std::fs::read_to_string;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+mod common;
+
+/// Local stand-in for the definitions API, answers the requests with the `responses` in order
+/// and returns the request lines it received.
+async fn serve(responses: Vec<(u16, String)>) -> anyhow::Result<(String, JoinHandle<Vec<String>>)> {
+  let listener = TcpListener::bind("127.0.0.1:0").await?;
+  let base_url = format!("http://{}", listener.local_addr()?);
+
+  let server = tokio::spawn(async move {
+    let mut requests = vec![];
+    for (status, body) in responses {
+      let (mut stream, _) = listener.accept().await.unwrap();
+
+      let mut request = vec![];
+      let mut buf = [0; 1024];
+      while !request.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+      }
+      let request = String::from_utf8(request).unwrap();
+      requests.push(request.lines().next().unwrap().to_string());
+
+      let response = format!(
+        "HTTP/1.1 {status} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+      );
+      stream.write_all(response.as_bytes()).await.unwrap();
+    }
+    requests
+  });
+
+  Ok((base_url, server))
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+  let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  dir
+}
+
+#[tokio::test]
+async fn definitions_are_cached_and_used_offline() -> anyhow::Result<()> {
+  let body = read_to_string(common::fixture_path(
+    "haptic_definitions/valid/RGgoCUyll84QnnNPGs1M.json",
+  ))?;
+  let cache_dir = temp_dir("bh-definitions-cache");
+
+  let (base_url, server) = serve(vec![(200, body)]).await?;
+  let client = HapticDefinitionsClient::new(base_url)
+    .with_cache_dir(Some(cache_dir.clone()))
+    .with_retry_backoff(Duration::from_millis(1));
+
+  let fetched = client.fetch("app", "key").await?;
+  assert_eq!(*fetched.version(), Some(10));
+  let requests = server.await?;
+  assert!(requests[0].contains("latest-version=-1"), "{requests:?}");
+  assert!(cache_dir.join("app").join("10.json").exists());
+
+  // the stand-in is gone, the cached definitions are used
+  let offline = client.fetch("app", "key").await?;
+  assert_eq!(offline, fetched);
+
+  std::fs::remove_dir_all(cache_dir)?;
+  Ok(())
+}
+
+#[tokio::test]
+async fn definitions_are_refetched_only_when_newer() -> anyhow::Result<()> {
+  let body = read_to_string(common::fixture_path(
+    "haptic_definitions/valid/RGgoCUyll84QnnNPGs1M.json",
+  ))?;
+  let newer = body.replacen("\"version\": 10", "\"version\": 11", 1);
+  let cache_dir = temp_dir("bh-definitions-refetch");
+
+  let (base_url, server) = serve(vec![(200, body.clone()), (200, body), (200, newer)]).await?;
+  let client = HapticDefinitionsClient::new(base_url)
+    .with_cache_dir(Some(cache_dir.clone()))
+    .with_retry_backoff(Duration::from_millis(1));
+
+  assert_eq!(*client.fetch("app", "key").await?.version(), Some(10));
+  assert_eq!(*client.fetch("app", "key").await?.version(), Some(10));
+  assert_eq!(*client.fetch("app", "key").await?.version(), Some(11));
+  assert_eq!(*client.cached("app")?.unwrap().version(), Some(11));
+
+  let requests = server.await?;
+  assert!(requests[1].contains("latest-version=10"), "{requests:?}");
+  assert!(requests[2].contains("latest-version=10"), "{requests:?}");
+
+  std::fs::remove_dir_all(cache_dir)?;
+  Ok(())
+}
+
+#[tokio::test]
+async fn rejected_credentials_are_not_retried() -> anyhow::Result<()> {
+  let body = r#"{"status":false,"code":401,"errorMessage":"invalid api key","timestamp":0}"#;
+  let (base_url, server) = serve(vec![(401, body.to_string())]).await?;
+  let client = HapticDefinitionsClient::new(base_url);
+
+  let err = client.fetch("app", "wrong").await.unwrap_err();
+  assert!(
+    matches!(
+      &err,
+      HapticDefinitionsError::InvalidCredentials { code: 401, message } if message == "invalid api key"
+    ),
+    "{err:?}"
+  );
+  assert_eq!(server.await?.len(), 1);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn rejected_requests_are_not_retried_nor_masked_by_the_cache() -> anyhow::Result<()> {
+  let body = read_to_string(common::fixture_path(
+    "haptic_definitions/valid/RGgoCUyll84QnnNPGs1M.json",
+  ))?;
+  let failure = r#"{"status":false,"code":422,"errorMessage":"bad app id","timestamp":0}"#;
+  let cache_dir = temp_dir("bh-definitions-rejected");
+
+  let (base_url, server) = serve(vec![(200, body), (400, failure.to_string())]).await?;
+  let client = HapticDefinitionsClient::new(base_url)
+    .with_cache_dir(Some(cache_dir.clone()))
+    .with_retry_backoff(Duration::from_millis(1));
+
+  client.fetch("app", "key").await?;
+  let err = client.fetch("app", "key").await.unwrap_err();
+  assert!(
+    matches!(&err, HapticDefinitionsError::Rejected { code: 400, message } if message == "bad app id"),
+    "{err:?}"
+  );
+  assert!(!err.is_transient());
+  assert_eq!(server.await?.len(), 2);
+
+  std::fs::remove_dir_all(cache_dir)?;
+  Ok(())
+}
+
+#[tokio::test]
+async fn transport_errors_do_not_show_the_api_key() -> anyhow::Result<()> {
+  // nothing listens on the port of a dropped listener
+  let listener = TcpListener::bind("127.0.0.1:0").await?;
+  let base_url = format!("http://{}", listener.local_addr()?);
+  drop(listener);
+  let client = HapticDefinitionsClient::new(base_url).with_retries(0);
+
+  let err = client.fetch("app", "secret-key").await.unwrap_err();
+  assert!(
+    matches!(err, HapticDefinitionsError::Transport(_)),
+    "{err:?}"
+  );
+  assert!(!format!("{err} {err:?}").contains("secret-key"), "{err:?}");
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() -> anyhow::Result<()> {
+  let body = read_to_string(common::fixture_path(
+    "haptic_definitions/valid/RGgoCUyll84QnnNPGs1M.json",
+  ))?;
+  let failure = r#"{"status":false,"code":500,"errorMessage":"oops","timestamp":0}"#.to_string();
+  let (base_url, server) = serve(vec![(200, failure.clone()), (502, failure), (200, body)]).await?;
+  let client = HapticDefinitionsClient::new(base_url).with_retry_backoff(Duration::from_millis(1));
+
+  assert_eq!(*client.fetch("app", "key").await?.version(), Some(10));
+  assert_eq!(server.await?.len(), 3);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn decode_errors_point_at_the_field() -> anyhow::Result<()> {
+  let body = r#"{"status":true,"code":200,"timestamp":0,"message":{"hapticMappings":[{"key":1}]}}"#;
+  let (base_url, _server) = serve(vec![(200, body.to_string())]).await?;
+  let client = HapticDefinitionsClient::new(base_url);
+
+  let err = client.fetch("app", "key").await.unwrap_err();
+  assert!(
+    matches!(&err, HapticDefinitionsError::Decode { path, .. } if path == "message.hapticMappings[0].key"),
+    "{err:?}"
+  );
+
+  Ok(())
+}
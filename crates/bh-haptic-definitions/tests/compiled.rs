@@ -0,0 +1,75 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{
+  CompiledPatterns, HapticDefinitionsMessage, HapticTimeline, RenderOptions, SdkApiResponseV3,
+};
+use std::fs::read_to_string;
+
+mod common;
+
+fn definitions() -> anyhow::Result<HapticDefinitionsMessage> {
+  let path = common::fixture_path("haptic_definitions/valid/684295032381e55e9210deb7.json");
+  let response =
+    serde_json::from_str::<SdkApiResponseV3<HapticDefinitionsMessage>>(&read_to_string(path)?)?;
+
+  Ok(response.message().clone().unwrap())
+}
+
+fn assert_close(actual: &HapticTimeline, expected: &HapticTimeline, key: &str) {
+  assert_eq!(
+    actual.duration_millis(),
+    expected.duration_millis(),
+    "{key}"
+  );
+
+  for (position, expected) in expected.positions() {
+    let actual = actual.position(position).unwrap();
+    for (tick, frame) in expected.frames().iter().enumerate() {
+      for (motor, intensity) in frame.iter().enumerate() {
+        assert!(
+          (actual.intensity(tick, motor) - intensity).abs() <= 0.5 / 255.0,
+          "{key} {position} {tick} {motor}"
+        );
+      }
+    }
+  }
+}
+
+#[test]
+fn compiled_definitions_play_as_rendered() -> anyhow::Result<()> {
+  let definitions = definitions()?;
+  let options = RenderOptions::default();
+  let compiled = definitions.compile(&options)?;
+  let loaded = CompiledPatterns::from_bytes(compiled.as_bytes())?;
+
+  assert_eq!(loaded.len(), definitions.haptic_mappings().len());
+  for mapping in definitions.haptic_mappings() {
+    let pattern = loaded.pattern(mapping.key()).unwrap();
+    assert_close(
+      &pattern.to_timeline(),
+      &mapping.render(&options),
+      mapping.key(),
+    );
+  }
+
+  Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn compiled_definitions_load_from_mapped_file() -> anyhow::Result<()> {
+  let definitions = definitions()?;
+  let compiled = definitions.compile(&RenderOptions::default())?;
+  let path = std::env::temp_dir().join(format!("bh-compiled-{}.bhcp", std::process::id()));
+  std::fs::write(&path, compiled.as_bytes())?;
+
+  // SAFETY: the file is private to this test and outlives the mapping
+  let mapped = unsafe { CompiledPatterns::open(&path)? };
+  assert_eq!(mapped.as_bytes(), compiled.as_bytes());
+  assert!(mapped.keys().eq(compiled.keys()));
+
+  drop(mapped);
+  std::fs::remove_file(&path)?;
+
+  Ok(())
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{
+  DevicePosition, HapticDefinitionMapping, HapticDefinitionTactFilePattern,
+  HapticDefinitionsMessage, MappingChange, SdkApiResponseV3, TactFile, TactFileProject, Track,
+};
+use std::fs::read_to_string;
+
+mod common;
+
+fn project(name: &str) -> TactFileProject {
+  let path = common::fixture_path("tact_file/valid/bonelab").join(format!("{name}.tact"));
+  let tact_file = TactFile::from_json(&read_to_string(path).unwrap()).unwrap();
+
+  tact_file.project().clone()
+}
+
+fn mapping(key: &str, patterns: Vec<(DevicePosition, TactFileProject)>) -> HapticDefinitionMapping {
+  let patterns = patterns
+    .into_iter()
+    .map(|(position, project)| HapticDefinitionTactFilePattern::new(position, project))
+    .collect();
+
+  HapticDefinitionMapping::new(key.to_string(), 100)
+    .with_enable(Some(true))
+    .with_intensity(Some(1.0))
+    .with_tact_file_patterns(patterns)
+}
+
+fn definitions(version: i64, mappings: Vec<HapticDefinitionMapping>) -> HapticDefinitionsMessage {
+  HapticDefinitionsMessage::new(mappings).with_version(Some(version))
+}
+
+#[test]
+fn haptic_definitions_diff_of_the_same_definitions_is_empty() -> anyhow::Result<()> {
+  let dir = common::fixture_path("haptic_definitions").join("valid");
+
+  for entry in std::fs::read_dir(dir)? {
+    let path = entry?.path();
+    let response =
+      serde_json::from_str::<SdkApiResponseV3<HapticDefinitionsMessage>>(&read_to_string(&path)?)?;
+    let definitions = response.message().clone().unwrap();
+
+    let diff = definitions.diff(&definitions);
+    assert!(diff.is_empty(), "{}: {diff:?}", path.display());
+    assert_eq!(diff.recompile_keys().count(), 0);
+  }
+
+  Ok(())
+}
+
+#[test]
+fn haptic_definitions_diff_lists_added_removed_and_modified_keys() {
+  let old = definitions(
+    1,
+    vec![
+      mapping("hit", vec![(DevicePosition::Vest, project("BulletHit"))]),
+      mapping("fall", vec![(DevicePosition::Vest, project("FallDamage"))]),
+      mapping("blade", vec![(DevicePosition::Vest, project("BladeHit"))]),
+    ],
+  );
+  let new = definitions(
+    2,
+    vec![
+      mapping("freeze", vec![(DevicePosition::Vest, project("FreezeHit"))]),
+      mapping("hit", vec![(DevicePosition::Vest, project("BulletHit"))]).with_enable(Some(false)),
+      mapping("blade", vec![(DevicePosition::Vest, project("BladeHit"))])
+        .with_intensity(Some(0.5))
+        .with_event_time(200),
+    ],
+  );
+
+  let diff = old.diff(&new);
+
+  assert_eq!(*diff.from_version(), Some(1));
+  assert_eq!(*diff.to_version(), Some(2));
+  assert_eq!(diff.added(), &["freeze".to_string()]);
+  assert_eq!(diff.removed(), &["fall".to_string()]);
+
+  let modified = diff
+    .modified()
+    .iter()
+    .map(|mapping| (mapping.key().as_str(), mapping.changes().clone()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    modified,
+    vec![
+      (
+        "hit",
+        vec![MappingChange::Enable {
+          from: true,
+          to: false
+        }]
+      ),
+      (
+        "blade",
+        vec![
+          MappingChange::Intensity {
+            from: Some(1.0),
+            to: Some(0.5)
+          },
+          MappingChange::EventTime { from: 100, to: 200 },
+        ]
+      ),
+    ]
+  );
+
+  // flipping the enable flag does not change the patterns
+  assert_eq!(
+    diff.recompile_keys().collect::<Vec<_>>(),
+    ["freeze", "blade"]
+  );
+}
+
+#[test]
+fn haptic_definition_mapping_diff_goes_down_to_the_tracks() {
+  let hit = project("BulletHit");
+  let track_count = hit.tracks().len();
+  let with_track = hit.clone().with_track(Track::new(vec![]));
+  let without_last_track = hit
+    .clone()
+    .with_tracks(hit.tracks()[..track_count - 1].to_vec());
+  let renamed = hit.clone().with_name(Some("renamed".to_string()));
+
+  let diff = |old: Vec<(DevicePosition, TactFileProject)>,
+              new: Vec<(DevicePosition, TactFileProject)>| {
+    mapping("hit", old).diff(&mapping("hit", new))
+  };
+
+  assert_eq!(
+    diff(
+      vec![(DevicePosition::Vest, hit.clone())],
+      vec![(DevicePosition::Vest, with_track)]
+    ),
+    vec![MappingChange::TrackAdded {
+      position: DevicePosition::Vest,
+      index: track_count,
+    }]
+  );
+  assert_eq!(
+    diff(
+      vec![(DevicePosition::Vest, hit.clone())],
+      vec![(DevicePosition::Vest, without_last_track)]
+    ),
+    vec![MappingChange::TrackRemoved {
+      position: DevicePosition::Vest,
+      index: track_count - 1,
+    }]
+  );
+  assert_eq!(
+    diff(
+      vec![(DevicePosition::Vest, hit.clone())],
+      vec![(DevicePosition::Vest, renamed)]
+    ),
+    vec![MappingChange::PatternModified {
+      position: DevicePosition::Vest,
+    }]
+  );
+
+  assert_eq!(
+    diff(
+      vec![(DevicePosition::Vest, hit)],
+      vec![(DevicePosition::Head, project("Headshot_F"))]
+    ),
+    vec![
+      MappingChange::PositionAdded {
+        position: DevicePosition::Head,
+      },
+      MappingChange::PositionRemoved {
+        position: DevicePosition::VestBack,
+      },
+      MappingChange::PositionRemoved {
+        position: DevicePosition::VestFront,
+      },
+      MappingChange::PatternAdded {
+        position: DevicePosition::Head,
+      },
+      MappingChange::PatternRemoved {
+        position: DevicePosition::Vest,
+      },
+    ]
+  );
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{DevicePosition, HapticDefinitionsRegistry, LocalDefinitions};
+use std::fs;
+
+mod common;
+
+#[test]
+fn local_workspace_is_assembled_from_tact_files() -> anyhow::Result<()> {
+  let root = std::env::temp_dir().join(format!("bh-local-definitions-{}", std::process::id()));
+  let dir = root.join("my-workspace");
+  fs::create_dir_all(&dir)?;
+
+  let fixture = |name: &str| common::fixture_path("tact_file/valid/bonelab").join(name);
+  fs::copy(fixture("BladeHit.tact"), dir.join("blade_hit.tact"))?;
+  fs::copy(fixture("FootStep_L.tact"), dir.join("step_LeftFoot.tact"))?;
+  fs::copy(fixture("BladeHit.tact"), dir.join("step_Vest.tact"))?;
+  fs::write(dir.join("notes.txt"), "not a pattern")?;
+
+  let local = LocalDefinitions::new(root.clone());
+  assert!(local.contains("my-workspace"));
+  assert!(!local.contains("other-workspace"));
+  assert!(!local.contains("../my-workspace"));
+
+  let definitions = local.load("my-workspace")?;
+  assert_eq!(definitions.workspace_id().as_deref(), Some("my-workspace"));
+  let registry = HapticDefinitionsRegistry::new(definitions);
+  assert_eq!(registry.len(), 2);
+
+  let blade_hit = registry.get("blade_hit").unwrap();
+  assert_eq!(
+    registry.positions("blade_hit"),
+    Some(vec![DevicePosition::VestBack, DevicePosition::VestFront])
+  );
+  assert_eq!(
+    *blade_hit.tact_file_patterns()[0].position(),
+    DevicePosition::Vest
+  );
+  assert!(*blade_hit.event_time() > 0);
+  assert_eq!(
+    *blade_hit.event_time(),
+    blade_hit.tact_file_patterns()[0]
+      .tact_file()
+      .duration_millis()
+  );
+
+  let step = registry.get("step").unwrap();
+  let positions = step
+    .tact_file_patterns()
+    .iter()
+    .map(|pattern| pattern.position().clone())
+    .collect::<Vec<_>>();
+  assert_eq!(positions, vec![DevicePosition::FootL, DevicePosition::Vest]);
+  let longest = step
+    .tact_file_patterns()
+    .iter()
+    .map(|pattern| pattern.tact_file().duration_millis())
+    .max();
+  assert_eq!(Some(*step.event_time()), longest);
+
+  fs::remove_dir_all(root)?;
+  Ok(())
+}
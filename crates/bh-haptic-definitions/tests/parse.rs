@@ -1,4 +1,9 @@
-use bh_haptic_definitions::{HapticDefinitionsMessage, SdkApiResponseV3, TactFile};
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{
+  DevicePosition, DeviceType, EffectMode, EffectPoint, HapticDefinitionTactFilePattern,
+  HapticDefinitionsMessage, HapticDefinitionsRegistry, RenderOptions, SdkApiResponseV3, TactFile,
+};
 use std::fs::read_to_string;
 
 mod common;
@@ -53,3 +58,252 @@ fn tact_files_parse_valid() -> anyhow::Result<()> {
 
   Ok(())
 }
+
+#[test]
+fn haptic_definitions_patterns_have_typed_positions() -> anyhow::Result<()> {
+  let path = common::fixture_path("haptic_definitions")
+    .join("valid")
+    .join("RGgoCUyll84QnnNPGs1M.json");
+  let data = read_to_string(path)?;
+
+  let parsed = serde_json::from_str::<SdkApiResponseV3<HapticDefinitionsMessage>>(&data)?;
+  let patterns = parsed
+    .message()
+    .as_ref()
+    .unwrap()
+    .haptic_mappings()
+    .iter()
+    .flat_map(|mapping| mapping.tact_file_patterns())
+    .collect::<Vec<_>>();
+
+  let arms = patterns
+    .iter()
+    .find(|pattern| pattern.position() == &DevicePosition::ForearmL)
+    .expect("a LeftArm pattern");
+  let modes = arms
+    .tact_file()
+    .tracks()
+    .iter()
+    .flat_map(|track| track.effects())
+    .flat_map(|effect| effect.modes().keys())
+    .collect::<Vec<_>>();
+  assert!(modes.contains(&&DevicePosition::ForearmR));
+
+  // names the crate does not know are kept as they are
+  let unknown = patterns
+    .iter()
+    .find(|pattern| pattern.position().is_unknown())
+    .expect("an Unknown pattern");
+  assert_eq!(
+    serde_json::to_value(unknown)?["position"],
+    serde_json::json!("Unknown")
+  );
+  assert_eq!(
+    serde_json::to_value(arms)?["position"],
+    serde_json::json!("LeftArm")
+  );
+
+  Ok(())
+}
+
+#[test]
+fn haptic_definitions_patterns_keep_position_names() -> anyhow::Result<()> {
+  let path = common::fixture_path("haptic_definitions")
+    .join("valid")
+    .join("RGgoCUyll84QnnNPGs1M.json");
+  let data = serde_json::from_str::<serde_json::Value>(&read_to_string(path)?)?;
+  let pattern = &data["message"]["hapticMappings"][0]["tactFilePatterns"][0];
+
+  for (name, position) in [
+    ("ForearmL", DevicePosition::ForearmL),
+    ("Head", DevicePosition::Head),
+    ("HandL", DevicePosition::HandL),
+    ("LeftArm", DevicePosition::ForearmL),
+    ("Face", DevicePosition::Head),
+  ] {
+    let mut named = pattern.clone();
+    named["position"] = serde_json::json!(name);
+
+    let parsed = serde_json::from_value::<HapticDefinitionTactFilePattern>(named)?;
+    assert_eq!(*parsed.position(), position, "Parsing {name}");
+    assert_eq!(
+      serde_json::to_value(&parsed)?["position"],
+      serde_json::json!(name),
+      "Writing {name}"
+    );
+  }
+
+  // patterns built in code are written with the legacy names
+  let built = HapticDefinitionTactFilePattern::new(
+    DevicePosition::HandL,
+    serde_json::from_value(pattern["tactFile"].clone())?,
+  );
+  assert_eq!(
+    serde_json::to_value(&built)?["position"],
+    serde_json::json!("LeftHand")
+  );
+
+  Ok(())
+}
+
+#[test]
+fn tact_file_layouts_match_catalogue() -> anyhow::Result<()> {
+  let dir = common::fixture_path("tact_file").join("valid");
+
+  for entry in walkdir::WalkDir::new(&dir) {
+    let entry = entry?;
+    if !entry.file_type().is_file() {
+      continue;
+    }
+    let name = entry.file_name().to_str().unwrap().to_string();
+    let tact_file = TactFile::from_json(&read_to_string(entry.path())?)?;
+    let layout = tact_file.project().layout();
+    let device_type = layout.r#type().parse::<DeviceType>()?;
+
+    for (position, points) in layout.layouts().iter().flatten() {
+      assert_eq!(
+        Some(points),
+        device_type.layout_points(position).as_ref(),
+        "{name}: {device_type} {position}"
+      );
+    }
+  }
+
+  Ok(())
+}
+
+#[test]
+fn haptic_definitions_keep_audio_file_patterns() -> anyhow::Result<()> {
+  let dir = common::fixture_path("haptic_definitions").join("valid");
+  let mut frame_count = 0;
+
+  for entry in walkdir::WalkDir::new(&dir) {
+    let entry = entry?;
+    if !entry.file_type().is_file() {
+      continue;
+    }
+    let name = entry.file_name().to_str().unwrap();
+    let data = read_to_string(entry.path())?;
+
+    let original = serde_json::from_str::<serde_json::Value>(&data)?;
+    let parsed = serde_json::from_str::<SdkApiResponseV3<HapticDefinitionsMessage>>(&data)?;
+    let message = parsed.message().as_ref().unwrap();
+
+    for pattern in message
+      .haptic_mappings()
+      .iter()
+      .flat_map(|mapping| mapping.audio_file_patterns())
+    {
+      for position in pattern.clip().patterns().keys() {
+        let frames = pattern.clip().frames(position)?;
+        assert!(frames.iter().all(|frame| frame.len() == 20), "{name}");
+        frame_count += frames.len();
+      }
+    }
+
+    let written = serde_json::to_value(message)?;
+    for (i, mapping) in original["message"]["hapticMappings"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .enumerate()
+    {
+      for field in ["id", "deployId", "audioFilePatterns"] {
+        assert_eq!(
+          written["hapticMappings"][i].get(field),
+          mapping.get(field),
+          "{name}: hapticMappings[{i}].{field}"
+        );
+      }
+    }
+  }
+
+  assert!(frame_count > 0);
+
+  Ok(())
+}
+
+#[test]
+fn tact_file_points_may_mix_the_forms() -> anyhow::Result<()> {
+  let data = r#"{
+    "project": {
+      "tracks": [{
+        "effects": [{
+          "startTime": 0,
+          "offsetTime": 100,
+          "modes": {
+            "VestFront": {
+              "mode": "DOT_MODE",
+              "dotMode": {
+                "feedback": [{
+                  "startTime": 0, "endTime": 100, "playbackType": "NONE",
+                  "pointList": [{ "x": 0.667, "y": 0.25, "intensity": 1 }]
+                }]
+              },
+              "pathMode": {
+                "feedback": [{
+                  "movingPattern": "CONST_TDM", "playbackType": "NONE", "visible": true,
+                  "pointList": [{ "index": 6, "time": 0, "intensity": "0.5" }]
+                }]
+              }
+            }
+          }
+        }]
+      }],
+      "layout": { "name": "Tactot", "type": "Tactot" }
+    }
+  }"#;
+
+  let tact_file = TactFile::from_json(data)?;
+  let written = serde_json::from_str::<serde_json::Value>(&tact_file.to_json()?)?;
+  let dot_point = &written["project"]["tracks"][0]["effects"][0]["modes"]["VestFront"]["dotMode"]["feedback"]
+    [0]["pointList"][0];
+  assert_eq!(
+    dot_point,
+    &serde_json::json!({ "x": 0.667, "y": 0.25, "intensity": 1.0 })
+  );
+
+  let project = tact_file.project();
+  let layout = project.layout().with_default_points(project.positions());
+  let timeline = project.render(&RenderOptions::default());
+  assert_eq!(
+    timeline
+      .position(&DevicePosition::VestFront)
+      .unwrap()
+      .intensity(0, 6),
+    1.0
+  );
+
+  let EffectMode::DotMode { dot_mode, .. } =
+    &project.tracks()[0].effects()[0].modes()[&DevicePosition::VestFront]
+  else {
+    panic!("Expected dot mode");
+  };
+  let point = &dot_mode.feedback()[0].point_list()[0];
+  assert_eq!(
+    point.to_dot(&layout, &DevicePosition::VestFront),
+    Some(EffectPoint::dot(6, 1.0))
+  );
+
+  Ok(())
+}
+
+#[test]
+fn haptic_definitions_registry_lists_positions() -> anyhow::Result<()> {
+  let path = common::fixture_path("haptic_definitions/valid/67ef1c5960d23e387d2e757e.json");
+  let response =
+    serde_json::from_str::<SdkApiResponseV3<HapticDefinitionsMessage>>(&read_to_string(path)?)?;
+  let registry = HapticDefinitionsRegistry::new(response.message().clone().unwrap());
+
+  for mapping in registry.definitions().haptic_mappings() {
+    let positions = registry.positions(mapping.key()).unwrap();
+    assert_eq!(
+      positions.is_empty(),
+      mapping.tact_file_patterns().is_empty() && mapping.audio_file_patterns().is_empty(),
+      "{}",
+      mapping.key()
+    );
+  }
+
+  Ok(())
+}
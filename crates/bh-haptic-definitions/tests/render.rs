@@ -0,0 +1,301 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{DevicePosition, RenderOptions, TactFile, TactFileProject};
+use std::fs::read_to_string;
+
+mod common;
+
+fn project_with_tracks(tracks: &str) -> TactFileProject {
+  let json = format!(
+    r#"{{
+      "tracks": {tracks},
+      "layout": {{
+        "name": "Tactot",
+        "type": "Tactot",
+        "layouts": {{
+          "VestFront": [
+            {{ "index": 0, "x": 0, "y": 0 }},
+            {{ "index": 1, "x": 1, "y": 0 }},
+            {{ "index": 2, "x": 0, "y": 1 }},
+            {{ "index": 3, "x": 1, "y": 1 }}
+          ]
+        }}
+      }}
+    }}"#
+  );
+
+  serde_json::from_str(&json).expect("valid test project")
+}
+
+fn dot_effect(start_time: u32, offset_time: u32, feedback: &str) -> String {
+  format!(
+    r#"{{
+      "startTime": {start_time},
+      "offsetTime": {offset_time},
+      "modes": {{
+        "VestFront": {{
+          "mode": "DOT_MODE",
+          "dotMode": {{ "dotConnected": false, "feedback": {feedback} }}
+        }}
+      }}
+    }}"#
+  )
+}
+
+#[test]
+fn dot_mode_renders_feedback_span() {
+  let effect = dot_effect(
+    0,
+    100,
+    r#"[{ "startTime": 40, "endTime": 100, "playbackType": "NONE",
+          "pointList": [{ "index": 1, "intensity": 0.5 }] }]"#,
+  );
+  let project = project_with_tracks(&format!(r#"[{{ "enable": true, "effects": [{effect}] }}]"#));
+
+  let timeline = project.render(&RenderOptions::default().with_tick_millis(20));
+  let vest = timeline.position(&DevicePosition::VestFront).unwrap();
+
+  assert_eq!(timeline.frame_count(), 5);
+  assert_eq!(*vest.motor_count(), 4);
+  assert_eq!(
+    (0..5)
+      .map(|tick| vest.intensity(tick, 1))
+      .collect::<Vec<_>>(),
+    vec![0.0, 0.0, 0.5, 0.5, 0.5]
+  );
+  assert_eq!(vest.intensity(2, 0), 0.0);
+}
+
+#[test]
+fn dot_mode_honours_effect_timing_and_disabled_tracks() {
+  let feedback = r#"[{ "startTime": 0, "endTime": 1000, "playbackType": "NONE",
+                       "pointList": [{ "index": 0, "intensity": 1 }] }]"#;
+  let shifted = dot_effect(100, 50, feedback);
+  let overlapping = dot_effect(
+    0,
+    200,
+    feedback
+      .replace("\"intensity\": 1", "\"intensity\": 0.25")
+      .as_str(),
+  );
+  let disabled = dot_effect(0, 400, feedback);
+
+  let project = project_with_tracks(&format!(
+    r#"[
+      {{ "enable": true, "effects": [{shifted}, {overlapping}] }},
+      {{ "enable": false, "effects": [{disabled}] }}
+    ]"#
+  ));
+
+  assert_eq!(project.duration_millis(), 200);
+
+  let timeline = project.render(&RenderOptions::default().with_tick_millis(50));
+  let vest = timeline.position(&DevicePosition::VestFront).unwrap();
+
+  // the shifted effect is cut by its `offsetTime`, the stronger one wins while overlapping
+  assert_eq!(
+    (0..timeline.frame_count())
+      .map(|tick| vest.intensity(tick, 0))
+      .collect::<Vec<_>>(),
+    vec![0.25, 0.25, 1.0, 0.25]
+  );
+}
+
+#[test]
+fn tact_files_render_valid() -> anyhow::Result<()> {
+  let dir = common::fixture_path("tact_file").join("valid");
+
+  for entry in walkdir::WalkDir::new(&dir) {
+    let entry = entry?;
+    if !entry.file_type().is_file() {
+      continue;
+    }
+    let path = entry.path();
+    let name = path.file_name().unwrap().to_str().unwrap();
+
+    let tact_file = serde_json::from_str::<TactFile>(&read_to_string(path)?)?;
+    let project = tact_file.project();
+    let timeline = project.render(&RenderOptions::default());
+
+    assert_eq!(
+      *timeline.duration_millis(),
+      project.duration_millis(),
+      "Unexpected duration of {name}"
+    );
+
+    for position in timeline.positions().values() {
+      assert_eq!(position.frames().len(), timeline.frame_count(), "{name}");
+    }
+  }
+
+  Ok(())
+}
+
+fn path_effect(moving_pattern: &str, points: &str) -> String {
+  format!(
+    r#"{{
+      "startTime": 0,
+      "offsetTime": 100,
+      "modes": {{
+        "VestFront": {{
+          "mode": "PATH_MODE",
+          "pathMode": {{
+            "feedback": [{{
+              "movingPattern": "{moving_pattern}",
+              "playbackType": "NONE",
+              "visible": true,
+              "pointList": {points}
+            }}]
+          }}
+        }}
+      }}
+    }}"#
+  )
+}
+
+#[test]
+fn path_mode_spreads_over_nearest_motors() {
+  let effect = path_effect(
+    "CONST_TDM",
+    r#"[{ "intensity": 1, "time": 0, "x": 0, "y": 0 },
+        { "intensity": 1, "time": 100, "x": 1, "y": 0 }]"#,
+  );
+  let project = project_with_tracks(&format!(r#"[{{ "effects": [{effect}] }}]"#));
+
+  let timeline = project.render(
+    &RenderOptions::default()
+      .with_tick_millis(50)
+      .with_path_motor_count(2),
+  );
+  let vest = timeline.position(&DevicePosition::VestFront).unwrap();
+
+  assert_eq!(vest.frame(0).unwrap(), &[1.0, 0.0, 0.0, 0.0]);
+  // right between the two top motors
+  assert_eq!(vest.frame(1).unwrap(), &[1.0, 1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn path_mode_moving_patterns() {
+  let points = r#"[{ "intensity": 1, "time": 0, "x": 0, "y": 0 },
+                   { "intensity": 1, "time": 10, "x": 1, "y": 0 },
+                   { "intensity": 1, "time": 100, "x": 1, "y": 1 }]"#;
+  let options = RenderOptions::default()
+    .with_tick_millis(10)
+    .with_path_motor_count(1);
+
+  let nearest_motors = |moving_pattern: &str| {
+    let effect = path_effect(moving_pattern, points);
+    let project = project_with_tracks(&format!(r#"[{{ "effects": [{effect}] }}]"#));
+    let timeline = project.render(&options);
+    let vest = timeline.position(&DevicePosition::VestFront).unwrap();
+
+    (0..timeline.frame_count())
+      .map(|tick| {
+        (0..4)
+          .find(|motor| vest.intensity(tick, *motor) > 0.0)
+          .unwrap()
+      })
+      .collect::<Vec<_>>()
+  };
+
+  // the first segment is as long as the second one, so it takes half of the time
+  assert_eq!(
+    nearest_motors("CONST_SPEED"),
+    vec![0, 0, 0, 1, 1, 1, 1, 1, 3, 3]
+  );
+  // the first segment takes 10ms only
+  assert_eq!(
+    nearest_motors("CONST_TDM"),
+    vec![0, 1, 1, 1, 1, 1, 3, 3, 3, 3]
+  );
+}
+
+#[test]
+fn playback_type_envelopes() {
+  let envelope = |playback_type: &str| {
+    let effect = dot_effect(
+      0,
+      100,
+      &format!(
+        r#"[{{ "startTime": 0, "endTime": 100, "playbackType": "{playback_type}",
+               "pointList": [{{ "index": 0, "intensity": 1 }}] }}]"#
+      ),
+    );
+    let project = project_with_tracks(&format!(r#"[{{ "effects": [{effect}] }}]"#));
+    let timeline = project.render(&RenderOptions::default().with_tick_millis(25));
+    let vest = timeline.position(&DevicePosition::VestFront).unwrap();
+
+    (0..timeline.frame_count())
+      .map(|tick| vest.intensity(tick, 0))
+      .collect::<Vec<_>>()
+  };
+
+  assert_eq!(envelope("NONE"), vec![1.0, 1.0, 1.0, 1.0]);
+  assert_eq!(envelope("FADE_IN"), vec![0.0, 0.25, 0.5, 0.75]);
+  assert_eq!(envelope("FADE_OUT"), vec![1.0, 0.75, 0.5, 0.25]);
+  assert_eq!(envelope("FADE_IN_OUT"), vec![0.0, 0.5, 1.0, 0.5]);
+}
+
+#[test]
+fn trimmed_projects_render_the_window_of_the_original() {
+  let dots = dot_effect(
+    0,
+    100,
+    r#"[{ "startTime": 0, "endTime": 100, "playbackType": "NONE",
+          "pointList": [{ "index": 0, "intensity": 1 }] },
+        { "startTime": 0, "endTime": 100, "playbackType": "FADE_OUT",
+          "pointList": [{ "index": 3, "intensity": 1 }] }]"#,
+  );
+  let path = path_effect(
+    "CONST_TDM",
+    r#"[{ "intensity": 0.5, "time": 0, "x": 0, "y": 0 },
+        { "intensity": 0.5, "time": 100, "x": 0, "y": 1 }]"#,
+  );
+  let project = project_with_tracks(&format!(
+    r#"[{{ "effects": [{dots}] }}, {{ "effects": [{path}] }}]"#
+  ));
+  let options = RenderOptions::default()
+    .with_tick_millis(25)
+    .with_path_motor_count(1);
+
+  let original = project.render(&options);
+  let original = original.position(&DevicePosition::VestFront).unwrap();
+  let trimmed = project.trimmed(25, 75).render(&options);
+  assert_eq!(trimmed.frame_count(), 2);
+  let trimmed = trimmed.position(&DevicePosition::VestFront).unwrap();
+
+  for tick in 0..2 {
+    for motor in 0..3 {
+      assert_eq!(
+        trimmed.intensity(tick, motor),
+        original.intensity(tick + 1, motor),
+        "{tick} {motor}"
+      );
+    }
+  }
+  // the fade is re-shaped to the remaining span instead of keeping its phase
+  assert_eq!(original.intensity(1, 3), 0.75);
+  assert_eq!(trimmed.intensity(0, 3), 1.0);
+  assert_eq!(trimmed.intensity(1, 3), 0.5);
+}
+
+#[test]
+fn missing_layouts_render_with_catalogue() {
+  let effect = path_effect(
+    "CONST_TDM",
+    r#"[{ "intensity": 1, "time": 0, "x": 0, "y": 0 }]"#,
+  );
+  let project = serde_json::from_str::<TactFileProject>(&format!(
+    r#"{{
+      "tracks": [{{ "effects": [{effect}] }}],
+      "layout": {{ "name": "Tactot", "type": "Tactot" }}
+    }}"#
+  ))
+  .unwrap();
+
+  let timeline = project.render(&RenderOptions::default().with_path_motor_count(1));
+  let vest = timeline.position(&DevicePosition::VestFront).unwrap();
+
+  assert_eq!(*vest.motor_count(), 20);
+  assert_eq!(vest.intensity(0, 0), 1.0);
+}
@@ -0,0 +1,357 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{
+  DevicePosition, DeviceType, EffectMode, EffectPathMode, EffectPathModeFeedback, EffectPoint,
+  HapticEffect, HapticTimeline, Layout, RenderOptions, RetargetMethod, TactFile, TactFileProject,
+  Track,
+};
+use std::fs::read_to_string;
+
+mod common;
+
+/// 4x5 motors on both the front and the back.
+fn vest_layout() -> Layout {
+  let path = common::fixture_path("tact_file/valid/bonelab/BulletHit.tact");
+  let tact_file = serde_json::from_str::<TactFile>(&read_to_string(path).unwrap()).unwrap();
+
+  tact_file.project().layout().clone()
+}
+
+fn vest_timeline(front: &[(usize, f64)], back: &[(usize, f64)]) -> HapticTimeline {
+  let mut timeline = HapticTimeline::new(20, 20);
+
+  for (position, motors) in [
+    (DevicePosition::VestFront, front),
+    (DevicePosition::VestBack, back),
+  ] {
+    let side = timeline.position_mut(position, 20);
+    for (motor, intensity) in motors {
+      side.set_intensity(0, *motor, *intensity);
+    }
+  }
+
+  timeline
+}
+
+fn active_motors(timeline: &HapticTimeline, position: DevicePosition) -> Vec<(usize, f64)> {
+  let frame = timeline.position(&position).unwrap().frame(0).unwrap();
+
+  frame
+    .iter()
+    .enumerate()
+    .filter(|(_, intensity)| **intensity > 0.0)
+    .map(|(motor, intensity)| (motor, *intensity))
+    .collect()
+}
+
+#[test]
+fn rotated_by_full_turn_is_unchanged() {
+  let timeline = vest_timeline(&[(5, 0.5)], &[(18, 1.0)]);
+
+  assert_eq!(timeline.rotated(&vest_layout(), 0.0, 0.0), timeline);
+  assert_eq!(timeline.rotated(&vest_layout(), 360.0, 0.0), timeline);
+}
+
+#[test]
+fn rotated_by_half_turn_swaps_sides() {
+  let timeline = vest_timeline(&[(0, 0.5), (7, 0.75)], &[(3, 1.0)]);
+  let rotated = timeline.rotated(&vest_layout(), 180.0, 0.0);
+
+  assert_eq!(
+    active_motors(&rotated, DevicePosition::VestBack),
+    vec![(0, 0.5), (7, 0.75)]
+  );
+  assert_eq!(
+    active_motors(&rotated, DevicePosition::VestFront),
+    vec![(3, 1.0)]
+  );
+}
+
+#[test]
+fn rotated_with_offset_moves_up_and_drops_overflow() {
+  let timeline = vest_timeline(&[(1, 0.5), (5, 1.0)], &[]);
+  let rotated = timeline.rotated(&vest_layout(), 0.0, 0.25);
+
+  // the second row becomes the first one, the first row is moved out of the vest
+  assert_eq!(
+    active_motors(&rotated, DevicePosition::VestFront),
+    vec![(1, 1.0)]
+  );
+}
+
+#[test]
+fn mirrored_timeline_flips_vest_and_swaps_sides() {
+  let mut timeline = vest_timeline(&[(0, 0.5), (9, 1.0)], &[]);
+  timeline
+    .position_mut(DevicePosition::ForearmL, 6)
+    .set_intensity(0, 2, 0.25);
+
+  let mirrored = timeline.mirrored(&vest_layout());
+
+  assert_eq!(
+    active_motors(&mirrored, DevicePosition::VestFront),
+    vec![(3, 0.5), (10, 1.0)]
+  );
+  assert_eq!(
+    active_motors(&mirrored, DevicePosition::ForearmR),
+    vec![(2, 0.25)]
+  );
+  assert!(mirrored.position(&DevicePosition::ForearmL).is_none());
+  assert_eq!(mirrored.mirrored(&vest_layout()), timeline);
+}
+
+fn load_project(rel: &str) -> TactFileProject {
+  let path = common::fixture_path("tact_file/valid").join(rel);
+
+  serde_json::from_str::<TactFile>(&read_to_string(path).unwrap())
+    .unwrap()
+    .project()
+    .clone()
+}
+
+#[test]
+fn mirrored_project_swaps_arms() {
+  let left = load_project("minehaptics/slash_8_left_tactosy.tact");
+  let right = load_project("minehaptics/slash_8_right_tactosy.tact");
+
+  assert_eq!(left.mirrored().tracks(), right.tracks());
+  assert_eq!(
+    left
+      .render(&RenderOptions::default())
+      .mirrored(left.layout()),
+    right.render(&RenderOptions::default())
+  );
+}
+
+#[test]
+fn mirrored_project_flips_vest() {
+  let left = load_project("bonelab/RecoilVest_L.tact");
+  let right = load_project("bonelab/RecoilVest_R.tact");
+  let options = RenderOptions::default();
+
+  assert_eq!(left.mirrored().render(&options), right.render(&options));
+  assert_eq!(
+    left.render(&options).mirrored(left.layout()),
+    right.render(&options)
+  );
+  assert_eq!(left.mirrored().mirrored(), left);
+}
+
+#[test]
+fn mirrored_project_without_layout_points_uses_catalogue() {
+  let left = load_project("bonelab/RecoilVest_L.tact");
+  let right = load_project("bonelab/RecoilVest_R.tact");
+  let without_points = left
+    .clone()
+    .with_layout(left.layout().clone().with_layouts(None));
+
+  let mirrored = without_points.mirrored();
+  assert_ne!(mirrored.tracks(), left.tracks());
+  assert_eq!(mirrored.tracks(), left.mirrored().tracks());
+  assert_eq!(
+    mirrored.render(&RenderOptions::default()),
+    right.render(&RenderOptions::default())
+  );
+}
+
+#[test]
+fn retargeted_project_renders_like_retargeted_timeline() {
+  let project = load_project("bonelab/RecoilVest_L.tact");
+  let x16 = DeviceType::TactSuitX16.layout();
+  let options = RenderOptions::default();
+
+  let retargeted = project.retargeted(&x16, RetargetMethod::NearestNeighbour);
+  assert_eq!(retargeted.layout().r#type(), "TactSuitX16");
+  assert_eq!(
+    retargeted.layout().motor_count(&DevicePosition::VestFront),
+    Some(8)
+  );
+
+  let timeline =
+    project
+      .render(&options)
+      .retargeted(project.layout(), &x16, RetargetMethod::NearestNeighbour);
+  assert_eq!(retargeted.render(&options), timeline);
+  assert!(
+    timeline
+      .positions()
+      .values()
+      .any(|position| !position.is_silent())
+  );
+}
+
+#[test]
+fn retargeted_timeline_keeps_positions_missing_in_target() {
+  let mut timeline = vest_timeline(&[(0, 1.0)], &[]);
+  timeline
+    .position_mut(DevicePosition::ForearmL, 6)
+    .set_intensity(0, 2, 0.5);
+
+  let retargeted = timeline.retargeted(
+    &vest_layout(),
+    &DeviceType::TactSuitX16.layout(),
+    RetargetMethod::AreaWeighted,
+  );
+
+  assert_eq!(
+    retargeted.position(&DevicePosition::ForearmL),
+    timeline.position(&DevicePosition::ForearmL)
+  );
+  assert_eq!(
+    *retargeted
+      .position(&DevicePosition::VestFront)
+      .unwrap()
+      .motor_count(),
+    8
+  );
+}
+
+#[test]
+fn retargeted_project_places_index_path_points_by_coordinates() {
+  let layout = vest_layout();
+  let path = EffectPathMode::new(vec![EffectPathModeFeedback::new(vec![
+    EffectPoint::dot(5, 1.0).with_time(Some(0)),
+    EffectPoint::path(0.5, 0.5, 100, 1.0),
+  ])]);
+  let project = TactFileProject::new(layout.clone()).with_track(Track::new(vec![
+    HapticEffect::new(0, 100).with_mode(DevicePosition::VestFront, path),
+  ]));
+
+  let retargeted = project.retargeted(
+    &DeviceType::TactSuitX16.layout(),
+    RetargetMethod::NearestNeighbour,
+  );
+
+  let effect = &retargeted.tracks()[0].effects()[0];
+  let Some(EffectMode::PathMode { path_mode, .. }) = effect.modes().get(&DevicePosition::VestFront)
+  else {
+    panic!("no path mode");
+  };
+  let points = path_mode.feedback()[0].point_list();
+  let motor = layout
+    .points(&DevicePosition::VestFront)
+    .unwrap()
+    .iter()
+    .find(|point| *point.index() == 5)
+    .unwrap();
+
+  assert_eq!(*points[0].index(), None);
+  assert_eq!(
+    (*points[0].x(), *points[0].y()),
+    (Some(*motor.x()), Some(*motor.y()))
+  );
+  assert_eq!(*points[0].time(), Some(0));
+  assert_eq!(points[1], EffectPoint::path(0.5, 0.5, 100, 1.0));
+}
+
+fn bonelab_project(name: &str) -> TactFileProject {
+  let path = common::fixture_path("tact_file/valid/bonelab").join(name);
+  let tact_file = serde_json::from_str::<TactFile>(&read_to_string(path).unwrap()).unwrap();
+
+  tact_file.project().clone()
+}
+
+#[test]
+fn appended_project_plays_after_the_first_one() {
+  let options = RenderOptions::default();
+  let hit = bonelab_project("BulletHit.tact");
+  let duration = hit.duration_millis();
+  // keep the second hit on the tick grid, so the frames compare exactly
+  let start = (duration + 100).next_multiple_of(*options.tick_millis());
+  let gap = start - duration;
+
+  let twice = hit.appended(&hit, gap);
+  assert_eq!(twice.duration_millis(), start + duration);
+  assert_eq!(twice.tracks().len(), 2 * hit.tracks().len());
+
+  let single = hit.render(&options);
+  let rendered = twice.render(&options);
+  let offset = rendered.tick_at(start);
+  for (position, timeline) in single.positions() {
+    let played = rendered.position(position).unwrap();
+    for tick in 0..single.frame_count() {
+      assert_eq!(
+        played.frame(tick),
+        timeline.frame(tick),
+        "{position} {tick}"
+      );
+      assert_eq!(
+        played.frame(offset + tick),
+        timeline.frame(tick),
+        "{position} {tick}"
+      );
+    }
+  }
+
+  assert_eq!(hit.looped(2, gap).render(&options), rendered);
+  assert_eq!(twice.trimmed(start, u32::MAX).render(&options), single);
+  assert_eq!(twice.shifted(-i64::from(start)).render(&options), single);
+}
+
+#[test]
+fn composed_project_times_saturate_at_the_end_of_time() {
+  let hit = bonelab_project("BulletHit.tact");
+
+  let shifted = hit.shifted(i64::MAX);
+  assert_eq!(shifted.duration_millis(), u32::MAX);
+  assert!(
+    shifted
+      .tracks()
+      .iter()
+      .flat_map(|track| track.effects())
+      .all(|effect| effect.start_millis() == u32::MAX)
+  );
+
+  assert_eq!(hit.appended(&hit, u32::MAX).duration_millis(), u32::MAX);
+  assert_eq!(hit.looped(3, u32::MAX).duration_millis(), u32::MAX);
+  assert_eq!(
+    hit.tracks()[0].repeated(3, u32::MAX / 2).duration_millis(),
+    u32::MAX
+  );
+  assert_eq!(shifted.trimmed(0, u32::MAX).duration_millis(), 0);
+
+  // a coarse tick keeps the timeline up to the end of time small
+  let options = RenderOptions::default().with_tick_millis(1 << 28);
+  let silent = |timeline: &HapticTimeline| {
+    timeline
+      .positions()
+      .values()
+      .all(|position| position.is_silent())
+  };
+
+  let timeline = shifted.render(&options);
+  assert_eq!(timeline.frame_count(), 16);
+  assert!(silent(&timeline));
+
+  let straddling = hit.shifted((u32::MAX - 10).into());
+  assert!(silent(&straddling.render(&options)));
+
+  let timeline = hit.looped(3, u32::MAX).render(&options);
+  assert_eq!(timeline.frame_count(), 16);
+  assert!(!silent(&timeline));
+  for (position, rendered) in hit.render(&options).positions() {
+    assert_eq!(
+      timeline.position(position).unwrap().frame(0),
+      rendered.frame(0)
+    );
+  }
+}
+
+#[test]
+fn layered_projects_keep_the_strongest_intensity() {
+  let options = RenderOptions::default();
+  let hit = bonelab_project("BulletHit.tact");
+  let louder = hit.layered([&hit.shifted(0)]);
+
+  assert_eq!(louder.tracks().len(), 2 * hit.tracks().len());
+  assert_eq!(louder.render(&options), hit.render(&options));
+
+  let trimmed = hit.trimmed(0, 40);
+  assert_eq!(trimmed.duration_millis(), 40.min(hit.duration_millis()));
+  assert_eq!(
+    hit
+      .trimmed(hit.duration_millis(), u32::MAX)
+      .duration_millis(),
+    0
+  );
+}
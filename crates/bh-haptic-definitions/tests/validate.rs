@@ -0,0 +1,163 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{
+  DevicePosition, DiagnosticKind, HapticDefinitionsMessage, SdkApiResponseV3, TactFile,
+  TactFileProject,
+};
+use std::fs::read_to_string;
+
+mod common;
+
+#[test]
+fn valid_fixtures_have_no_errors() -> anyhow::Result<()> {
+  let dirs = ["haptic_definitions", "tact_file"].map(|dir| common::fixture_path(dir).join("valid"));
+
+  for entry in dirs.iter().flat_map(walkdir::WalkDir::new) {
+    let entry = entry?;
+    if !entry.file_type().is_file() {
+      continue;
+    }
+    let path = entry.path();
+    let name = path.file_name().unwrap().to_str().unwrap();
+    let data = read_to_string(path)?;
+
+    let diagnostics = match path.extension().and_then(|ext| ext.to_str()) {
+      Some("tact") => TactFile::from_json(&data)?.project().validate(),
+      _ => serde_json::from_str::<SdkApiResponseV3<HapticDefinitionsMessage>>(&data)?
+        .message()
+        .as_ref()
+        .unwrap()
+        .validate(),
+    };
+
+    let errors = diagnostics
+      .iter()
+      .filter(|diagnostic| diagnostic.is_error())
+      .map(ToString::to_string)
+      .collect::<Vec<_>>();
+    assert!(errors.is_empty(), "{name}: {errors:#?}");
+  }
+
+  Ok(())
+}
+
+#[test]
+fn diagnostics_point_to_json_paths() -> anyhow::Result<()> {
+  let project = serde_json::from_str::<TactFileProject>(
+    r#"{
+      "tracks": [{
+        "effects": [{
+          "startTime": 0,
+          "offsetTime": 100,
+          "modes": {
+            "VestFront": {
+              "mode": "DOT_MODE",
+              "dotMode": {
+                "feedback": [{
+                  "startTime": 50, "endTime": 10, "playbackType": "NONE",
+                  "pointList": [{ "index": 0, "intensity": 1 }, { "index": 7, "intensity": 1.5 }]
+                }]
+              }
+            },
+            "Tail": {
+              "mode": "PATH_MODE",
+              "pathMode": {
+                "feedback": [{
+                  "movingPattern": "CONST_TDM", "playbackType": "NONE", "visible": true,
+                  "pointList": []
+                }]
+              }
+            }
+          }
+        }]
+      }],
+      "layout": {
+        "name": "Tactot",
+        "type": "Tactot",
+        "layouts": { "VestFront": [{ "index": 0, "x": 0, "y": 0 }] }
+      }
+    }"#,
+  )?;
+
+  let diagnostics = project
+    .validate()
+    .into_iter()
+    .map(|diagnostic| (diagnostic.path().clone(), diagnostic.kind().clone()))
+    .collect::<Vec<_>>();
+
+  let effect = "$.tracks[0].effects[0].modes";
+  assert_eq!(
+    diagnostics,
+    vec![
+      (
+        format!("{effect}.Tail"),
+        DiagnosticKind::UnknownModeKey {
+          key: "Tail".to_string()
+        }
+      ),
+      (
+        format!("{effect}.Tail.pathMode.feedback[0].pointList"),
+        DiagnosticKind::EmptyPointList
+      ),
+      (
+        format!("{effect}.VestFront.dotMode.feedback[0].endTime"),
+        DiagnosticKind::EndBeforeStart {
+          start_time: 50,
+          end_time: 10
+        }
+      ),
+      (
+        format!("{effect}.VestFront.dotMode.feedback[0].pointList[1].index"),
+        DiagnosticKind::UnknownDotIndex {
+          position: DevicePosition::VestFront,
+          index: 7
+        }
+      ),
+      (
+        format!("{effect}.VestFront.dotMode.feedback[0].pointList[1].intensity"),
+        DiagnosticKind::IntensityOutOfRange { intensity: 1.5 }
+      ),
+    ]
+  );
+
+  Ok(())
+}
+
+#[test]
+fn event_time_must_match_pattern_length() -> anyhow::Result<()> {
+  let tact_file = |offset_time: u32| {
+    format!(
+      r#"{{
+        "position": "Vest",
+        "tactFile": {{
+          "tracks": [{{ "effects": [{{ "startTime": 0, "offsetTime": {offset_time}, "modes": {{}} }}] }}],
+          "layout": {{ "name": "Tactot", "type": "Tactot" }}
+        }}
+      }}"#
+    )
+  };
+  let definitions = serde_json::from_str::<HapticDefinitionsMessage>(&format!(
+    r#"{{
+      "hapticMappings": [
+        {{ "key": "hit", "intensity": 100, "eventTime": 500, "tactFilePatterns": [{}] }},
+        {{ "key": "heal", "intensity": 100, "eventTime": 500, "tactFilePatterns": [{}] }}
+      ]
+    }}"#,
+    tact_file(490),
+    tact_file(1000)
+  ))?;
+
+  let diagnostics = definitions.validate();
+
+  assert_eq!(diagnostics.len(), 1);
+  assert_eq!(diagnostics[0].path(), "$.hapticMappings[1].eventTime");
+  assert_eq!(
+    diagnostics[0].kind(),
+    &DiagnosticKind::EventTimeMismatch {
+      event_time: 500,
+      pattern_millis: 1000
+    }
+  );
+
+  Ok(())
+}
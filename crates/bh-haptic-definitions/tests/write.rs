@@ -0,0 +1,207 @@
+#![cfg(feature = "serde")]
+
+use bh_haptic_definitions::{
+  DevicePosition, DeviceType, EffectDotMode, EffectDotModeFeedback, EffectFeedbackPlaybackType,
+  EffectPathMode, EffectPathModeFeedback, EffectPoint, HapticEffect, RenderOptions, TactFile,
+  TactFileProject, Track,
+};
+use serde_json::Value;
+use std::fs::read_to_string;
+
+mod common;
+
+/// Makes the JSON comparable regardless of the number formatting (`0` vs `0.0`) and missing vs
+/// `null` fields.
+fn normalize(value: Value) -> Value {
+  match value {
+    Value::Number(number) => Value::from(number.as_f64().unwrap()),
+    Value::Array(items) => Value::Array(items.into_iter().map(normalize).collect()),
+    Value::Object(fields) => Value::Object(
+      fields
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| (key, normalize(value)))
+        .collect(),
+    ),
+    other => other,
+  }
+}
+
+#[test]
+fn tact_files_round_trip_valid() -> anyhow::Result<()> {
+  let dir = common::fixture_path("tact_file").join("valid");
+
+  for entry in walkdir::WalkDir::new(&dir) {
+    let entry = entry?;
+    if !entry.file_type().is_file() {
+      continue;
+    }
+    let path = entry.path();
+    let name = path.file_name().unwrap().to_str().unwrap();
+    let data = read_to_string(path)?;
+
+    let parsed = TactFile::from_json(&data)?;
+    let written = parsed.to_json()?;
+    let reparsed = TactFile::from_json(&written)?;
+
+    assert_eq!(parsed, reparsed, "Round trip changed {name}");
+    assert_eq!(written, reparsed.to_json()?, "Writing {name} is not stable");
+    assert_eq!(
+      normalize(serde_json::from_str(&data)?),
+      normalize(serde_json::from_str(&written)?),
+      "Written {name} differs from the original"
+    );
+  }
+
+  Ok(())
+}
+
+#[test]
+fn written_tact_file_keeps_what_is_not_played() -> anyhow::Result<()> {
+  let data = serde_json::json!({
+    "project": {
+      "id": 1234,
+      "tracks": [{
+        "effects": [{
+          "startTime": 0,
+          "offsetTime": 100,
+          "modes": {
+            "VestFront": {
+              "mode": "DOT_MODE",
+              "dotMode": {
+                "dotConnected": false,
+                "feedback": [{
+                  "startTime": 0,
+                  "endTime": 100,
+                  "playbackType": "NONE",
+                  "pointList": [{ "index": 0, "intensity": 1, "selected": true }],
+                  "locked": true
+                }],
+                "color": "red"
+              },
+              "pathMode": {
+                "feedback": [{
+                  "playbackType": "FADE_OUT",
+                  "movingPattern": "CONST_TDM",
+                  "visible": true,
+                  "pointList": [{ "x": 0.5, "y": 0.5, "time": 0, "intensity": 0.5 }],
+                  "speed": 2
+                }]
+              }
+            }
+          }
+        }]
+      }],
+      "layout": { "name": "Tactot", "type": "Tactot" }
+    }
+  });
+
+  let parsed = serde_json::from_value::<TactFile>(data.clone())?;
+  let written: Value = serde_json::from_str(&parsed.to_json()?)?;
+
+  assert_eq!(written["project"]["id"], 1234);
+  assert_eq!(normalize(written), normalize(data));
+
+  Ok(())
+}
+
+#[test]
+fn written_tact_file_has_designer_shape() -> anyhow::Result<()> {
+  let data = r#"{
+    "project": {
+      "Tracks": [{
+        "effects": [{
+          "startTime": 0,
+          "offsetTime": 100,
+          "trackIndex": 0,
+          "modes": {
+            "VestFront": {
+              "mode": "DOT_MODE",
+              "dotMode": { "dotConnected": false, "feedback": [] }
+            },
+            "Tail": {
+              "mode": "DOT_MODE",
+              "dotMode": { "dotConnected": false, "feedback": [] }
+            }
+          }
+        }]
+      }],
+      "Layout": { "name": "Tactot", "type": "Tactot" },
+      "tags": ["hit"]
+    }
+  }"#;
+
+  let written: Value = serde_json::from_str(&TactFile::from_json(data)?.to_json()?)?;
+  let project = &written["project"];
+
+  assert!(project.get("Tracks").is_none());
+  assert_eq!(project["tags"], serde_json::json!(["hit"]));
+  assert_eq!(project["layout"]["name"], "Tactot");
+  // absent fields are not written as `null`
+  assert!(project.get("id").is_none());
+
+  let effect = &project["tracks"][0]["effects"][0];
+  assert_eq!(effect["trackIndex"], 0);
+  assert_eq!(
+    effect["modes"]["VestFront"]["pathMode"],
+    serde_json::json!({ "feedback": [] })
+  );
+  assert_eq!(effect["modes"]["Tail"]["mode"], "DOT_MODE");
+
+  Ok(())
+}
+
+#[test]
+fn built_tact_file_validates_and_round_trips() -> anyhow::Result<()> {
+  // recoil: a fading kick on the upper chest, then a short swipe along the forearm
+  let kick = (0..4)
+    .map(|step| {
+      let intensity = 1.0 - f64::from(step) * 0.25;
+      EffectDotModeFeedback::new(
+        step * 40,
+        (step + 1) * 40,
+        (0..4)
+          .map(|index| EffectPoint::dot(index, intensity))
+          .collect(),
+      )
+    })
+    .collect();
+  let swipe = EffectPathModeFeedback::new(vec![
+    EffectPoint::path(0.0, 0.5, 0, 0.8),
+    EffectPoint::path(1.0, 0.5, 100, 0.4),
+  ])
+  .with_playback_type(EffectFeedbackPlaybackType::FadeOut);
+
+  let project = TactFileProject::new(DeviceType::TactSuitX40.layout())
+    .with_name(Some("Recoil".to_string()))
+    .with_track(
+      Track::new(vec![])
+        .with_effect(
+          HapticEffect::new(0, 160).with_mode(DevicePosition::VestFront, EffectDotMode::new(kick)),
+        )
+        .with_effect(
+          HapticEffect::new(160, 100)
+            .with_mode(DevicePosition::ForearmR, EffectPathMode::new(vec![swipe])),
+        ),
+    );
+
+  assert_eq!(project.validate(), vec![]);
+  assert_eq!(project.duration_millis(), 260);
+
+  let timeline = project.render(&RenderOptions::default());
+  let vest = timeline.position(&DevicePosition::VestFront).unwrap();
+  assert_eq!(vest.intensity(0, 0), 1.0);
+  assert_eq!(vest.intensity(2, 0), 0.75);
+  assert!(
+    !timeline
+      .position(&DevicePosition::ForearmR)
+      .unwrap()
+      .is_silent()
+  );
+
+  let tact_file = TactFile::new(project);
+  let written = tact_file.to_json()?;
+  assert_eq!(TactFile::from_json(&written)?, tact_file);
+
+  Ok(())
+}
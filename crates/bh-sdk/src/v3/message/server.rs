@@ -1,5 +1,6 @@
+use bh_haptic_definitions::{DevicePosition, DeviceType};
 use derivative::Derivative;
-use getset::Getters;
+use getset::{Getters, WithSetters};
 use strum::{EnumDiscriminants, EnumString, VariantNames};
 
 #[derive(Derivative, EnumDiscriminants)]
@@ -49,12 +50,13 @@ impl ServerEventListMessageItem {
   }
 }
 
-#[derive(Derivative, Getters)]
-#[get = "pub"]
+#[derive(Derivative, Getters, WithSetters)]
+#[getset(get = "pub", set_with = "pub")]
 #[derivative(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ServerDevicesMessageItem {
+  /// See [DevicePosition::code].
   position: u32,
   device_name: String,
   address: String,
@@ -68,6 +70,32 @@ pub struct ServerDevicesMessageItem {
   vsm: u32,
 }
 
+impl ServerDevicesMessageItem {
+  /// Connected and paired device, `None` if the position has no code.
+  pub fn new(device_type: &DeviceType, position: &DevicePosition, address: String) -> Option<Self> {
+    Some(Self {
+      position: position.code()?,
+      device_name: device_type.device_name(position),
+      address,
+      connected: true,
+      paired: true,
+      battery: 100,
+      audio_jack_in: false,
+      vsm: 0,
+    })
+  }
+
+  pub fn device_type(&self) -> Option<DeviceType> {
+    DeviceType::from_device_name(&self.device_name).map(|(device_type, _)| device_type)
+  }
+
+  /// Position from the `position` code, or from the device name if the code is not known.
+  pub fn device_position(&self) -> Option<DevicePosition> {
+    DevicePosition::from_code(self.position)
+      .or_else(|| DeviceType::from_device_name(&self.device_name).map(|(_, position)| position))
+  }
+}
+
 #[cfg(feature = "serde")]
 impl<'de> serde::de::Deserialize<'de> for ServerMessage {
   fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -175,5 +203,48 @@ mod tests {
     let msg = serde_json::from_str::<ServerMessage>(json);
 
     assert!(msg.is_ok(), "Failed to parse JSON: {:?}", msg.unwrap_err());
+
+    let ServerMessage::ServerDevices(devices) = msg.unwrap() else {
+      panic!("Expected ServerDevices");
+    };
+    let typed = devices
+      .iter()
+      .map(|device| (device.device_type(), device.device_position()))
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      typed,
+      vec![
+        (Some(DeviceType::TactSuitX40), Some(DevicePosition::Vest)),
+        (Some(DeviceType::Tactosy2), Some(DevicePosition::ForearmL)),
+        (Some(DeviceType::Tactosy2), Some(DevicePosition::ForearmR)),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_builds_server_devices_from_typed_values() {
+    let device = ServerDevicesMessageItem::new(
+      &DeviceType::Tactosy2,
+      &DevicePosition::ForearmR,
+      "FC8A5696C0B8".to_string(),
+    )
+    .unwrap()
+    .with_battery(62);
+
+    assert_eq!(*device.position(), 2);
+    assert_eq!(device.device_name(), "Tactosy2 (R)");
+    assert_eq!(device.device_type(), Some(DeviceType::Tactosy2));
+    assert_eq!(device.device_position(), Some(DevicePosition::ForearmR));
+    assert_eq!(*device.battery(), 62);
+
+    assert!(
+      ServerDevicesMessageItem::new(
+        &DeviceType::Tactosy2,
+        &DevicePosition::Unknown("Tail".to_string()),
+        String::new(),
+      )
+      .is_none()
+    );
   }
 }
@@ -1,6 +1,6 @@
 use ss_bh::server::ws::{BhWebsocketServerBuilder, BhWebsocketServerConfig};
 
-use ss_bh::server::HapticManagerCommand;
+use ss_bh::server::{HapticManager, HapticManagerCommand};
 use std::path::PathBuf;
 use tokio::sync::{broadcast, mpsc};
 use tokio_util::sync::CancellationToken;
@@ -25,25 +25,21 @@ async fn main() -> anyhow::Result<()> {
 
   let cancellation_token = CancellationToken::new();
 
-  let (command_sender, mut command_receiver) = mpsc::channel::<HapticManagerCommand>(10);
+  let (command_sender, command_receiver) = mpsc::channel::<HapticManagerCommand>(10);
   let (event_sender, _event_receiver) = broadcast::channel::<ss_bh::server::HapticManagerEvent>(10);
 
+  let manager = HapticManager::new(command_receiver, event_sender.clone())
+    .with_cancellation_token(Some(cancellation_token.clone()));
+  let manager_handle = tokio::spawn(manager.run());
+
   BhWebsocketServerBuilder::new(ws_config, command_sender, event_sender)
-    .with_cancellation_token(Some(cancellation_token))
+    .with_cancellation_token(Some(cancellation_token.clone()))
     .build()
     .await?;
 
-  loop {
-    tokio::select! {
-      Some(command) = command_receiver.recv() => {
-        info!("Received command: {:?}", command);
-      },
-      _ = tokio::signal::ctrl_c() => {
-        println!("Received Ctrl+C, shutting down.");
-        break;
-      }
-    }
-  }
-
-  Ok(())
+  tokio::signal::ctrl_c().await?;
+  info!("Received Ctrl+C, shutting down.");
+  cancellation_token.cancel();
+
+  manager_handle.await?
 }
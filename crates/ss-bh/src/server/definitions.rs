@@ -0,0 +1,264 @@
+use async_trait::async_trait;
+use bh_haptic_definitions::{
+  DefinitionsCache, HapticDefinitionsClient, HapticDefinitionsError, HapticDefinitionsMessage,
+  LocalDefinitions,
+};
+use derivative::Derivative;
+use getset::Getters;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use tracing::*;
+
+/// What the SDK authenticates with, the providers pick whichever identifies their definitions.
+///
+/// The ids are not interchangeable: the cloud and cached definitions are keyed by the `app_id`,
+/// the local and in-memory ones by the `workspace_id`. The v4 clients do not send an app id, so
+/// their `app_id` is their `workspace_id`.
+#[derive(Derivative, Getters)]
+#[derivative(Debug, Clone, PartialEq, Eq)]
+#[get = "pub"]
+pub struct DefinitionsRequest {
+  /// Id the app connects to the server with.
+  workspace_id: String,
+  /// Id the app is registered with in the bHaptics API.
+  app_id: String,
+  #[derivative(Debug = "ignore")]
+  api_key: String,
+}
+
+impl DefinitionsRequest {
+  pub fn new(workspace_id: String, app_id: String, api_key: String) -> Self {
+    Self {
+      workspace_id,
+      app_id,
+      api_key,
+    }
+  }
+}
+
+/// Source of the haptic definitions of the connecting apps.
+#[async_trait]
+pub trait DefinitionsProvider: Debug + Send + Sync {
+  /// `Ok(None)` if the provider does not have the definitions, the next provider of
+  /// a [ChainedDefinitionsProvider] is asked then.
+  ///
+  /// Errors, which are [HapticDefinitionsError]s, are reported to the SDK as such.
+  async fn definitions(
+    &self,
+    request: &DefinitionsRequest,
+  ) -> anyhow::Result<Option<HapticDefinitionsMessage>>;
+}
+
+/// The bHaptics API, see [HapticDefinitionsClient].
+#[derive(Debug, Clone, Default)]
+pub struct CloudDefinitionsProvider {
+  client: HapticDefinitionsClient,
+}
+
+impl CloudDefinitionsProvider {
+  pub fn new(client: HapticDefinitionsClient) -> Self {
+    Self { client }
+  }
+}
+
+#[async_trait]
+impl DefinitionsProvider for CloudDefinitionsProvider {
+  async fn definitions(
+    &self,
+    request: &DefinitionsRequest,
+  ) -> anyhow::Result<Option<HapticDefinitionsMessage>> {
+    let definitions = self
+      .client
+      .fetch(request.app_id(), request.api_key())
+      .await?;
+    Ok(Some(definitions))
+  }
+}
+
+/// Definitions fetched before, as they were cached by a [HapticDefinitionsClient] with the same
+/// `cache_dir`, by the `app_id`. Unreadable caches are skipped.
+///
+/// Meant for servers without network, asked before the cloud it serves outdated versions and
+/// revoked credentials. The client itself already falls back to its cache on transient errors.
+#[derive(Debug, Clone)]
+pub struct CachedDefinitionsProvider {
+  cache: DefinitionsCache,
+}
+
+impl CachedDefinitionsProvider {
+  pub fn new(cache: DefinitionsCache) -> Self {
+    Self { cache }
+  }
+}
+
+#[async_trait]
+impl DefinitionsProvider for CachedDefinitionsProvider {
+  async fn definitions(
+    &self,
+    request: &DefinitionsRequest,
+  ) -> anyhow::Result<Option<HapticDefinitionsMessage>> {
+    let app_id = request.app_id();
+    Ok(self.cache.newest(app_id).unwrap_or_else(|err| {
+      warn!("Ignoring the cached haptic definitions of {app_id}: {err:#}");
+      None
+    }))
+  }
+}
+
+/// Workspaces from `.tact` files, see [LocalDefinitions].
+#[derive(Debug, Clone)]
+pub struct LocalDefinitionsProvider {
+  local: LocalDefinitions,
+}
+
+impl LocalDefinitionsProvider {
+  pub fn new(local: LocalDefinitions) -> Self {
+    Self { local }
+  }
+}
+
+#[async_trait]
+impl DefinitionsProvider for LocalDefinitionsProvider {
+  async fn definitions(
+    &self,
+    request: &DefinitionsRequest,
+  ) -> anyhow::Result<Option<HapticDefinitionsMessage>> {
+    let workspace_id = request.workspace_id();
+    if !self.local.contains(workspace_id) {
+      return Ok(None);
+    }
+
+    info!(
+      "Loading haptic definitions of {workspace_id} from {}",
+      self.local.root().display()
+    );
+    self.local.load(workspace_id).map(Some)
+  }
+}
+
+/// Definitions by the workspace id.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryDefinitionsProvider {
+  definitions: HashMap<String, HapticDefinitionsMessage>,
+}
+
+impl InMemoryDefinitionsProvider {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_workspace(
+    mut self,
+    workspace_id: String,
+    definitions: HapticDefinitionsMessage,
+  ) -> Self {
+    self.definitions.insert(workspace_id, definitions);
+    self
+  }
+}
+
+#[async_trait]
+impl DefinitionsProvider for InMemoryDefinitionsProvider {
+  async fn definitions(
+    &self,
+    request: &DefinitionsRequest,
+  ) -> anyhow::Result<Option<HapticDefinitionsMessage>> {
+    Ok(self.definitions.get(request.workspace_id()).cloned())
+  }
+}
+
+/// Asks the providers in order and returns the first definitions found, e.g. local → cloud.
+/// Errors are returned right away.
+#[derive(Debug, Clone, Default)]
+pub struct ChainedDefinitionsProvider {
+  providers: Vec<Arc<dyn DefinitionsProvider>>,
+}
+
+impl ChainedDefinitionsProvider {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Appends the provider, it is asked after the ones added before.
+  pub fn then(mut self, provider: impl DefinitionsProvider + 'static) -> Self {
+    self.providers.push(Arc::new(provider));
+    self
+  }
+}
+
+#[async_trait]
+impl DefinitionsProvider for ChainedDefinitionsProvider {
+  async fn definitions(
+    &self,
+    request: &DefinitionsRequest,
+  ) -> anyhow::Result<Option<HapticDefinitionsMessage>> {
+    for provider in &self.providers {
+      if let Some(definitions) = provider.definitions(request).await? {
+        return Ok(Some(definitions));
+      }
+    }
+
+    Ok(None)
+  }
+}
+
+/// Definitions of the request, failing with [HapticDefinitionsError::NotFound] if the provider
+/// does not have them.
+pub async fn provide_definitions(
+  provider: &dyn DefinitionsProvider,
+  request: &DefinitionsRequest,
+) -> anyhow::Result<HapticDefinitionsMessage> {
+  let definitions = provider.definitions(request).await?;
+  definitions.ok_or_else(|| {
+    anyhow::Error::new(HapticDefinitionsError::NotFound {
+      code: 404,
+      message: format!("No haptic definitions of {}", request.workspace_id()),
+    })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn request(workspace_id: &str) -> DefinitionsRequest {
+    DefinitionsRequest::new(
+      workspace_id.to_string(),
+      "app".to_string(),
+      "key".to_string(),
+    )
+  }
+
+  #[tokio::test]
+  async fn chain_returns_the_first_definitions_found() {
+    let first = HapticDefinitionsMessage::new(vec![]).with_name(Some("first".to_string()));
+    let second = HapticDefinitionsMessage::new(vec![]).with_name(Some("second".to_string()));
+
+    let provider = ChainedDefinitionsProvider::new()
+      .then(InMemoryDefinitionsProvider::new().with_workspace("a".to_string(), first.clone()))
+      .then(
+        InMemoryDefinitionsProvider::new()
+          .with_workspace("a".to_string(), second.clone())
+          .with_workspace("b".to_string(), second.clone()),
+      );
+
+    assert_eq!(
+      provider.definitions(&request("a")).await.unwrap(),
+      Some(first)
+    );
+    assert_eq!(
+      provider.definitions(&request("b")).await.unwrap(),
+      Some(second)
+    );
+    assert_eq!(provider.definitions(&request("c")).await.unwrap(), None);
+
+    let err = provide_definitions(&provider, &request("c"))
+      .await
+      .unwrap_err();
+    assert!(matches!(
+      err.downcast_ref::<HapticDefinitionsError>(),
+      Some(HapticDefinitionsError::NotFound { .. })
+    ));
+  }
+}
@@ -0,0 +1,454 @@
+use bh_haptic_definitions::{
+  HapticDefinitionMapping, HapticDefinitionsRegistry, capped_duration_scale,
+};
+use getset::{Getters, WithSetters};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::future::FutureExt;
+use tokio_util::sync::CancellationToken;
+use tracing::*;
+
+use super::{HapticEvent, HapticManagerCommand, HapticManagerEvent};
+
+/// Event started by [HapticManagerCommand::PlayEvent], tracked until its pattern has finished.
+#[derive(Debug, Clone, Getters)]
+#[get = "pub"]
+pub struct ActiveHapticEvent {
+  event_name: String,
+  request_id: u32,
+
+  /// Client-side start time, as sent by the SDK.
+  start_millis: u64,
+
+  intensity: f64,
+  duration: f64,
+  offset_x: f64,
+  offset_y: f64,
+
+  started_at: Instant,
+  expires_at: Instant,
+}
+
+impl ActiveHapticEvent {
+  pub fn is_active_at(&self, now: Instant) -> bool {
+    now < self.expires_at
+  }
+}
+
+/// Per-namespace (workspace/application) state of the [HapticManager].
+#[derive(Debug, Default, Clone, Getters)]
+#[get = "pub"]
+pub struct HapticNamespace {
+  registry: Option<HapticDefinitionsRegistry>,
+  active_events: Vec<ActiveHapticEvent>,
+}
+
+impl HapticNamespace {
+  /// Enabled events of the registered definitions.
+  pub fn events(&self) -> Vec<HapticEvent> {
+    self
+      .registry
+      .iter()
+      .flat_map(|registry| registry.enabled())
+      .map(HapticEvent::from)
+      .collect()
+  }
+
+  pub fn find_mapping(&self, event_name: &str) -> Option<&HapticDefinitionMapping> {
+    self.registry.as_ref()?.get(event_name)
+  }
+
+  pub fn active_event_names(&self, now: Instant) -> Vec<String> {
+    let mut names = Vec::<String>::new();
+    for event in self.active_events.iter().filter(|e| e.is_active_at(now)) {
+      if !names.contains(&event.event_name) {
+        names.push(event.event_name.clone());
+      }
+    }
+    names
+  }
+
+  pub fn active_request_ids(&self, now: Instant) -> Vec<u32> {
+    self
+      .active_events
+      .iter()
+      .filter(|event| event.is_active_at(now))
+      .map(|event| event.request_id)
+      .collect()
+  }
+
+  fn prune(&mut self, now: Instant) {
+    self.active_events.retain(|event| event.is_active_at(now));
+  }
+}
+
+/// Consumes [HapticManagerCommand]s produced by the protocol handlers, keeps the haptic
+/// definitions of every namespace and the events currently playing in it.
+#[derive(Debug, WithSetters)]
+pub struct HapticManager {
+  command_receiver: mpsc::Receiver<HapticManagerCommand>,
+  event_sender: broadcast::Sender<HapticManagerEvent>,
+
+  namespaces: HashMap<String, HapticNamespace>,
+
+  #[getset(set_with = "pub")]
+  cancellation_token: Option<CancellationToken>,
+}
+
+impl HapticManager {
+  pub fn new(
+    command_receiver: mpsc::Receiver<HapticManagerCommand>,
+    event_sender: broadcast::Sender<HapticManagerEvent>,
+  ) -> Self {
+    Self {
+      command_receiver,
+      event_sender,
+      namespaces: HashMap::new(),
+      cancellation_token: None,
+    }
+  }
+
+  pub fn namespace(&self, namespace: &str) -> Option<&HapticNamespace> {
+    self.namespaces.get(namespace)
+  }
+
+  /// Processes commands until the command channel is closed or the token is cancelled.
+  pub async fn run(mut self) -> anyhow::Result<()> {
+    let cancellation_token = self.cancellation_token.take().unwrap_or_default();
+
+    while let Some(Some(command)) = self
+      .command_receiver
+      .recv()
+      .with_cancellation_token(&cancellation_token)
+      .await
+    {
+      if let Err(err) = self.handle_command(command) {
+        error!("Failed to handle haptic manager command: {err}");
+      }
+    }
+
+    info!("Haptic manager exited gracefully");
+
+    Ok(())
+  }
+
+  #[instrument(skip(self))]
+  pub fn handle_command(&mut self, command: HapticManagerCommand) -> anyhow::Result<()> {
+    let now = Instant::now();
+
+    match command {
+      HapticManagerCommand::ClientConnected { namespace } => {
+        let state = self.namespaces.entry(namespace.clone()).or_default();
+        state.prune(now);
+
+        // reconnecting clients still expect the list of already registered events
+        if state.registry.is_some() {
+          let events = state.events();
+          self.emit(HapticManagerEvent::HapticEventsUpdated { namespace, events });
+        }
+
+        Ok(())
+      }
+      HapticManagerCommand::RegisterHapticDefinitions {
+        namespace,
+        definitions,
+      } => {
+        let state = self.namespaces.entry(namespace.clone()).or_default();
+        state.registry = Some(HapticDefinitionsRegistry::new(*definitions));
+
+        // patterns of the previous definitions are gone, so are their playbacks
+        let registered = state.events();
+        state.active_events.retain(|active| {
+          registered
+            .iter()
+            .any(|event| event.name() == &active.event_name)
+        });
+
+        self.emit(HapticManagerEvent::HapticEventsUpdated {
+          namespace,
+          events: registered,
+        });
+
+        Ok(())
+      }
+      HapticManagerCommand::PlayEvent {
+        namespace,
+        event_name,
+        request_id,
+        start_millis,
+        intensity,
+        duration,
+        offset_x,
+        offset_y,
+      } => {
+        let state = self
+          .namespaces
+          .get_mut(&namespace)
+          .ok_or_else(|| anyhow::anyhow!("Unknown namespace: {namespace}"))?;
+        state.prune(now);
+
+        let mapping = state
+          .find_mapping(&event_name)
+          .ok_or_else(|| anyhow::anyhow!("Unknown event {event_name} in {namespace}"))?;
+
+        if !mapping.is_enabled() {
+          debug!("Event {event_name} is disabled, ignoring");
+          return Ok(());
+        }
+
+        let duration = capped_duration_scale(duration)
+          .map_err(|err| anyhow::anyhow!("{err} of {event_name} in {namespace}"))?;
+        let millis = f64::from(*mapping.event_time()) * duration;
+        let expires_at = Duration::try_from_secs_f64(millis / 1000.0)
+          .ok()
+          .and_then(|length| now.checked_add(length))
+          .ok_or_else(|| anyhow::anyhow!("Invalid duration {duration} of {event_name}"))?;
+
+        // the same request id means the SDK re-submitted the request
+        state
+          .active_events
+          .retain(|event| event.request_id != request_id);
+        state.active_events.push(ActiveHapticEvent {
+          event_name,
+          request_id,
+          start_millis,
+          intensity,
+          duration,
+          offset_x,
+          offset_y,
+          started_at: now,
+          expires_at,
+        });
+
+        Ok(())
+      }
+      HapticManagerCommand::StopAll { namespace } => {
+        if let Some(state) = self.namespaces.get_mut(&namespace) {
+          state.active_events.clear();
+        }
+
+        Ok(())
+      }
+    }
+  }
+
+  fn emit(&self, event: HapticManagerEvent) {
+    // no subscribers is a valid state, e.g. when no client is connected yet
+    if let Err(err) = self.event_sender.send(event) {
+      debug!("No subscribers for haptic manager event: {err}");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use bh_haptic_definitions::{HapticDefinitionsMessage, MAX_DURATION_SCALE};
+
+  fn create_test_manager() -> (
+    HapticManager,
+    mpsc::Sender<HapticManagerCommand>,
+    broadcast::Receiver<HapticManagerEvent>,
+  ) {
+    let (command_tx, command_rx) = mpsc::channel(10);
+    let (event_tx, event_rx) = broadcast::channel(10);
+
+    (
+      HapticManager::new(command_rx, event_tx),
+      command_tx,
+      event_rx,
+    )
+  }
+
+  fn create_test_definitions() -> HapticDefinitionsMessage {
+    create_test_definitions_with(&[("hit", 500, None), ("heal", 0, Some(false))])
+  }
+
+  fn create_test_definitions_with(
+    events: &[(&str, u32, Option<bool>)],
+  ) -> HapticDefinitionsMessage {
+    HapticDefinitionsMessage::new(
+      events
+        .iter()
+        .map(|(key, event_time, enable)| {
+          HapticDefinitionMapping::new(key.to_string(), *event_time).with_enable(*enable)
+        })
+        .collect(),
+    )
+  }
+
+  fn play(namespace: &str, event_name: &str, request_id: u32) -> HapticManagerCommand {
+    HapticManagerCommand::PlayEvent {
+      namespace: namespace.to_string(),
+      event_name: event_name.to_string(),
+      request_id,
+      start_millis: 0,
+      intensity: 1.0,
+      duration: 1.0,
+      offset_x: 0.0,
+      offset_y: 0.0,
+    }
+  }
+
+  #[test]
+  fn test_register_emits_enabled_events() {
+    let (mut manager, _command_tx, mut event_rx) = create_test_manager();
+
+    manager
+      .handle_command(HapticManagerCommand::RegisterHapticDefinitions {
+        namespace: "test-workspace".to_string(),
+        definitions: Box::new(create_test_definitions()),
+      })
+      .unwrap();
+
+    match event_rx.try_recv().unwrap() {
+      HapticManagerEvent::HapticEventsUpdated { namespace, events } => {
+        assert_eq!(namespace, "test-workspace");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name(), "hit");
+        assert_eq!(*events[0].event_time(), 500);
+      }
+    }
+  }
+
+  #[test]
+  fn test_client_connected_replays_registered_events() {
+    let (mut manager, _command_tx, mut event_rx) = create_test_manager();
+
+    let connected = HapticManagerCommand::ClientConnected {
+      namespace: "test-workspace".to_string(),
+    };
+
+    manager.handle_command(connected.clone()).unwrap();
+    assert!(event_rx.try_recv().is_err(), "Nothing registered yet");
+
+    manager
+      .handle_command(HapticManagerCommand::RegisterHapticDefinitions {
+        namespace: "test-workspace".to_string(),
+        definitions: Box::new(create_test_definitions()),
+      })
+      .unwrap();
+    event_rx.try_recv().unwrap();
+
+    manager.handle_command(connected).unwrap();
+    assert!(matches!(
+      event_rx.try_recv().unwrap(),
+      HapticManagerEvent::HapticEventsUpdated { .. }
+    ));
+  }
+
+  #[test]
+  fn test_play_and_stop_all_track_active_events() {
+    let (mut manager, _command_tx, _event_rx) = create_test_manager();
+
+    manager
+      .handle_command(HapticManagerCommand::RegisterHapticDefinitions {
+        namespace: "test-workspace".to_string(),
+        definitions: Box::new(create_test_definitions()),
+      })
+      .unwrap();
+
+    manager
+      .handle_command(play("test-workspace", "hit", 1))
+      .unwrap();
+    manager
+      .handle_command(play("test-workspace", "hit", 2))
+      .unwrap();
+    manager
+      .handle_command(play("test-workspace", "hit", 2))
+      .unwrap();
+
+    let now = Instant::now();
+    let state = manager.namespace("test-workspace").unwrap();
+    assert_eq!(state.active_request_ids(now), vec![1, 2]);
+    assert_eq!(state.active_event_names(now), vec!["hit"]);
+
+    // disabled and unknown events are not played
+    manager
+      .handle_command(play("test-workspace", "heal", 3))
+      .unwrap();
+    assert!(
+      manager
+        .handle_command(play("test-workspace", "miss", 4))
+        .is_err()
+    );
+    assert!(
+      manager
+        .handle_command(play("other-workspace", "hit", 5))
+        .is_err()
+    );
+
+    manager
+      .handle_command(HapticManagerCommand::StopAll {
+        namespace: "test-workspace".to_string(),
+      })
+      .unwrap();
+
+    let state = manager.namespace("test-workspace").unwrap();
+    assert!(state.active_request_ids(Instant::now()).is_empty());
+  }
+
+  #[test]
+  fn test_play_rejects_invalid_durations_and_caps_long_ones() {
+    let (mut manager, _command_tx, _event_rx) = create_test_manager();
+
+    manager
+      .handle_command(HapticManagerCommand::RegisterHapticDefinitions {
+        namespace: "test-workspace".to_string(),
+        definitions: Box::new(create_test_definitions()),
+      })
+      .unwrap();
+
+    for (request_id, duration) in (1..).zip([f64::INFINITY, f64::NAN, -1.0, 1e300]) {
+      let command = HapticManagerCommand::PlayEvent {
+        namespace: "test-workspace".to_string(),
+        event_name: "hit".to_string(),
+        request_id,
+        start_millis: 0,
+        intensity: 1.0,
+        duration,
+        offset_x: 0.0,
+        offset_y: 0.0,
+      };
+      assert_eq!(
+        manager.handle_command(command).is_ok(),
+        duration.is_finite() && duration > 0.0,
+        "{duration}"
+      );
+    }
+
+    let state = manager.namespace("test-workspace").unwrap();
+    assert_eq!(state.active_request_ids(Instant::now()), vec![4]);
+    let event = &state.active_events()[0];
+    assert_eq!(event.duration, MAX_DURATION_SCALE);
+    assert_eq!(
+      event.expires_at - event.started_at,
+      Duration::from_millis(500 * MAX_DURATION_SCALE as u64)
+    );
+  }
+
+  #[tokio::test]
+  async fn test_run_exits_on_cancellation() {
+    let (manager, command_tx, mut event_rx) = create_test_manager();
+    let token = CancellationToken::new();
+
+    let handle = tokio::spawn(manager.with_cancellation_token(Some(token.clone())).run());
+
+    command_tx
+      .send(HapticManagerCommand::RegisterHapticDefinitions {
+        namespace: "test-workspace".to_string(),
+        definitions: Box::new(create_test_definitions()),
+      })
+      .await
+      .unwrap();
+
+    assert!(matches!(
+      event_rx.recv().await.unwrap(),
+      HapticManagerEvent::HapticEventsUpdated { .. }
+    ));
+
+    token.cancel();
+    assert!(handle.await.unwrap().is_ok());
+  }
+}
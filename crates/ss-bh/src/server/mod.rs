@@ -1,7 +1,15 @@
-use bh_haptic_definitions::HapticDefinitionsMessage;
+use bh_haptic_definitions::{HapticDefinitionMapping, HapticDefinitionsMessage};
 use derivative::Derivative;
 use getset::Getters;
 
+#[cfg(feature = "v3")]
+mod definitions;
+mod manager;
+
+#[cfg(feature = "v3")]
+pub use definitions::*;
+pub use manager::*;
+
 #[cfg(feature = "ws")]
 pub mod ws;
 
@@ -18,6 +26,12 @@ impl HapticEvent {
   }
 }
 
+impl From<&HapticDefinitionMapping> for HapticEvent {
+  fn from(mapping: &HapticDefinitionMapping) -> Self {
+    Self::new(mapping.key().clone(), *mapping.event_time())
+  }
+}
+
 #[derive(Derivative, Debug, Clone)]
 pub enum HapticManagerCommand {
   ClientConnected {
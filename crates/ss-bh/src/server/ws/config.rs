@@ -1,3 +1,5 @@
+#[cfg(feature = "v3")]
+use bh_haptic_definitions::{HapticDefinitionsClient, LocalDefinitions};
 use derivative::Derivative;
 use getset::{Getters, WithSetters};
 use std::net::{Ipv4Addr, SocketAddr};
@@ -20,6 +22,17 @@ pub struct BhWebsocketServerConfig {
   #[cfg(feature = "tls")]
   #[getset(set_with = "pub")]
   tls_key_path: Option<PathBuf>,
+
+  /// Endpoint and cache of the haptic definitions fetched for the v3/v4 clients, unless the
+  /// server is built with another definitions provider.
+  #[cfg(feature = "v3")]
+  #[getset(set_with = "pub")]
+  definitions: HapticDefinitionsClient,
+
+  /// Workspaces served from `.tact` files instead of the endpoint, see [LocalDefinitions].
+  #[cfg(feature = "v3")]
+  #[getset(set_with = "pub")]
+  local_definitions: Option<LocalDefinitions>,
 }
 
 impl Default for BhWebsocketServerConfig {
@@ -35,6 +48,12 @@ impl Default for BhWebsocketServerConfig {
 
       #[cfg(feature = "tls")]
       tls_key_path: Some(PathBuf::from("./certs/key.pem")),
+
+      #[cfg(feature = "v3")]
+      definitions: HapticDefinitionsClient::default(),
+
+      #[cfg(feature = "v3")]
+      local_definitions: None,
     }
   }
 }
@@ -1,15 +1,24 @@
 use super::{HandlerBuilder, MessageHandler};
-use crate::server::{HapticManagerCommand, HapticManagerEvent};
-use axum::extract::ws::Message;
-use bh_haptic_definitions::{HapticDefinitionsMessage, fetch_haptic_definitions};
+use crate::server::{
+  CloudDefinitionsProvider, DefinitionsProvider, DefinitionsRequest, HapticManagerCommand,
+  HapticManagerEvent, provide_definitions,
+};
+use axum::extract::ws::{CloseFrame, Message, close_code};
+use bh_haptic_definitions::{HapticDefinitionsError, HapticDefinitionsMessage};
 use bh_sdk::v3::{SdkMessage, ServerEventListMessageItem, ServerMessage};
 use derive_more::Display;
 use getset::Getters;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use tracing::*;
 
+/// Close code of the connections of apps with an invalid id or API key.
+pub const CLOSE_INVALID_CREDENTIALS: u16 = 4401;
+/// Close code of the connections of apps without a deployed workspace.
+pub const CLOSE_WORKSPACE_NOT_FOUND: u16 = 4404;
+
 #[derive(Clone, Debug, Display, Getters, Serialize, Deserialize)]
 #[display("AppContext {{ workspace_id={workspace_id}, api_key=*****, version={version:?} }}")]
 #[get = "pub"]
@@ -33,6 +42,7 @@ pub struct FeedbackHandlerBuilder {
   app_ctx: AppContext,
   command_sender: mpsc::Sender<HapticManagerCommand>,
   ws_sender: mpsc::UnboundedSender<Message>,
+  definitions: Arc<dyn DefinitionsProvider>,
   cancellation_token: Option<CancellationToken>,
 }
 
@@ -49,6 +59,7 @@ impl HandlerBuilder for FeedbackHandlerBuilder {
       app_ctx: context,
       command_sender,
       ws_sender,
+      definitions: Arc::new(CloudDefinitionsProvider::default()),
       cancellation_token: None,
     }
   }
@@ -65,14 +76,23 @@ impl HandlerBuilder for FeedbackHandlerBuilder {
       app_ctx: self.app_ctx,
       command_sender: self.command_sender,
       ws_sender: self.ws_sender,
+      definitions: self.definitions,
     })
   }
 }
 
+impl FeedbackHandlerBuilder {
+  pub fn with_definitions(mut self, definitions: Arc<dyn DefinitionsProvider>) -> Self {
+    self.definitions = definitions;
+    self
+  }
+}
+
 pub struct FeedbackHandler {
   app_ctx: AppContext,
   command_sender: mpsc::Sender<HapticManagerCommand>,
   ws_sender: mpsc::UnboundedSender<Message>,
+  definitions: Arc<dyn DefinitionsProvider>,
 }
 
 impl MessageHandler for FeedbackHandler {
@@ -145,24 +165,32 @@ impl FeedbackHandler {
   pub(crate) async fn handle_sdk_message(&mut self, msg: &SdkMessage) -> anyhow::Result<()> {
     match msg {
       SdkMessage::SdkRequestAuth(msg) => {
-        let haptic_definitions =
-          fetch_haptic_definitions(msg.application_id(), msg.sdk_api_key()).await?;
+        let fetched = self
+          .fetch_definitions(msg.application_id(), msg.sdk_api_key())
+          .await;
 
-        self.init(haptic_definitions).await
+        match fetched {
+          Ok(haptic_definitions) => self.init(haptic_definitions).await,
+          Err(err) => self.reject(err),
+        }
       }
       SdkMessage::SdkRequestAuthInit(msg) => {
-        let haptic_definitions = match msg.haptic().message() {
-          Some(defs) => defs.clone(),
+        let fetched = match msg.haptic().message() {
+          Some(defs) => Ok(defs.clone()),
           None => {
-            fetch_haptic_definitions(
-              msg.authentication().application_id(),
-              msg.authentication().sdk_api_key(),
-            )
-            .await?
+            self
+              .fetch_definitions(
+                msg.authentication().application_id(),
+                msg.authentication().sdk_api_key(),
+              )
+              .await
           }
         };
 
-        self.init(haptic_definitions).await
+        match fetched {
+          Ok(haptic_definitions) => self.init(haptic_definitions).await,
+          Err(err) => self.reject(err),
+        }
       }
       SdkMessage::SdkStopAll => self
         .command_sender
@@ -188,6 +216,42 @@ impl FeedbackHandler {
     }
   }
 
+  async fn fetch_definitions(
+    &self,
+    app_id: &str,
+    api_key: &str,
+  ) -> anyhow::Result<HapticDefinitionsMessage> {
+    let request = DefinitionsRequest::new(
+      self.app_ctx.workspace_id().to_string(),
+      app_id.to_string(),
+      api_key.to_string(),
+    );
+    provide_definitions(self.definitions.as_ref(), &request).await
+  }
+
+  /// Closes the connection with the reason the definitions could not be fetched, the SDK has no
+  /// message for it.
+  fn reject(&self, err: anyhow::Error) -> anyhow::Result<()> {
+    let code = match err.downcast_ref::<HapticDefinitionsError>() {
+      Some(HapticDefinitionsError::InvalidCredentials { .. }) => CLOSE_INVALID_CREDENTIALS,
+      Some(HapticDefinitionsError::NotFound { .. }) => CLOSE_WORKSPACE_NOT_FOUND,
+      _ => close_code::ERROR,
+    };
+
+    // close reasons are limited to 123 bytes
+    let mut reason = err.to_string();
+    while reason.len() > 123 {
+      reason.pop();
+    }
+
+    self.ws_sender.send(Message::Close(Some(CloseFrame {
+      code,
+      reason: reason.into(),
+    })))?;
+
+    Err(err.context("Failed to fetch haptic definitions"))
+  }
+
   async fn init(&self, haptic_definitions: HapticDefinitionsMessage) -> anyhow::Result<()> {
     self
       .command_sender
@@ -222,8 +286,10 @@ impl FeedbackHandler {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::server::HapticEvent;
-  use bh_sdk::v3::SdkPlayWithStartTimeMessage;
+  use crate::server::{HapticEvent, InMemoryDefinitionsProvider, LocalDefinitionsProvider};
+  use bh_haptic_definitions::LocalDefinitions;
+  use bh_sdk::v3::{SdkPlayWithStartTimeMessage, SdkRequestAuthMessage};
+  use std::path::PathBuf;
   use tokio::sync::mpsc;
 
   fn create_test_app_context() -> AppContext {
@@ -247,11 +313,46 @@ mod tests {
       app_ctx,
       command_sender: command_tx,
       ws_sender: ws_tx,
+      definitions: Arc::new(InMemoryDefinitionsProvider::new()),
     };
 
     (handler, command_rx, ws_rx)
   }
 
+  fn create_test_auth_message() -> SdkMessage {
+    SdkMessage::SdkRequestAuth(SdkRequestAuthMessage::new(
+      String::new(),
+      "test-app".to_string(),
+      String::new(),
+      String::new(),
+      "test-api-key".to_string(),
+    ))
+  }
+
+  /// Directory removed when dropped, even if the test fails.
+  struct TestDir(PathBuf);
+
+  impl TestDir {
+    fn new(name: &str) -> Self {
+      let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+      std::fs::create_dir_all(&dir).unwrap();
+      Self(dir)
+    }
+  }
+
+  impl Drop for TestDir {
+    fn drop(&mut self) {
+      let _ = std::fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn expect_close_code(ws_rx: &mut mpsc::UnboundedReceiver<Message>) -> u16 {
+    match ws_rx.try_recv() {
+      Ok(Message::Close(Some(frame))) => frame.code,
+      other => panic!("Expected close message, got {other:?}"),
+    }
+  }
+
   #[tokio::test]
   async fn test_send_message_serializes_and_sends() {
     let (handler, _command_rx, mut ws_rx) = create_test_handler();
@@ -389,6 +490,111 @@ mod tests {
     }
   }
 
+  #[tokio::test]
+  async fn test_handle_sdk_request_auth_uses_local_definitions() {
+    let (mut handler, mut command_rx, _ws_rx) = create_test_handler();
+
+    let root = TestDir::new("ss-bh-local");
+    std::fs::create_dir_all(root.0.join("test-workspace")).unwrap();
+    handler.definitions = Arc::new(LocalDefinitionsProvider::new(LocalDefinitions::new(
+      root.0.clone(),
+    )));
+
+    let result = handler
+      .handle_sdk_message(&create_test_auth_message())
+      .await;
+    assert!(result.is_ok());
+
+    let command = command_rx.recv().await.unwrap();
+    assert!(matches!(
+      command,
+      HapticManagerCommand::ClientConnected { .. }
+    ));
+    let command = command_rx.recv().await.unwrap();
+    match command {
+      HapticManagerCommand::RegisterHapticDefinitions { definitions, .. } => {
+        assert_eq!(
+          definitions.workspace_id().as_deref(),
+          Some("test-workspace")
+        );
+      }
+      _ => panic!("Expected RegisterHapticDefinitions command"),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_handle_sdk_request_auth_uses_in_memory_definitions() {
+    let (mut handler, mut command_rx, mut ws_rx) = create_test_handler();
+
+    let definitions = HapticDefinitionsMessage::new(vec![]).with_id(Some("test-id".to_string()));
+    handler.definitions = Arc::new(
+      InMemoryDefinitionsProvider::new()
+        .with_workspace("test-workspace".to_string(), definitions.clone()),
+    );
+
+    let result = handler
+      .handle_sdk_message(&create_test_auth_message())
+      .await;
+    assert!(result.is_ok());
+
+    assert!(matches!(
+      command_rx.recv().await.unwrap(),
+      HapticManagerCommand::ClientConnected { .. }
+    ));
+    match command_rx.recv().await.unwrap() {
+      HapticManagerCommand::RegisterHapticDefinitions {
+        definitions: received_defs,
+        ..
+      } => assert_eq!(*received_defs, definitions),
+      _ => panic!("Expected RegisterHapticDefinitions command"),
+    }
+    match ws_rx.recv().await.unwrap() {
+      Message::Text(text) => {
+        let parsed: ServerMessage = serde_json::from_str(&text).unwrap();
+        assert!(matches!(parsed, ServerMessage::ServerReady));
+      }
+      _ => panic!("Expected text message"),
+    }
+  }
+
+  #[tokio::test]
+  async fn test_handle_sdk_request_auth_rejects_unknown_workspace() {
+    let (mut handler, mut command_rx, mut ws_rx) = create_test_handler();
+
+    let result = handler
+      .handle_sdk_message(&create_test_auth_message())
+      .await;
+    assert!(result.is_err());
+
+    assert_eq!(expect_close_code(&mut ws_rx), CLOSE_WORKSPACE_NOT_FOUND);
+    assert!(
+      command_rx.try_recv().is_err(),
+      "Should not register the client"
+    );
+  }
+
+  #[test]
+  fn test_reject_closes_with_the_code_of_the_error() {
+    let (handler, _command_rx, mut ws_rx) = create_test_handler();
+
+    let err = HapticDefinitionsError::InvalidCredentials {
+      code: 401,
+      message: "test".to_string(),
+    };
+    assert!(handler.reject(err.into()).is_err());
+    assert_eq!(expect_close_code(&mut ws_rx), CLOSE_INVALID_CREDENTIALS);
+
+    let err = HapticDefinitionsError::NotFound {
+      code: 404,
+      message: "test".to_string(),
+    };
+    assert!(handler.reject(err.into()).is_err());
+    assert_eq!(expect_close_code(&mut ws_rx), CLOSE_WORKSPACE_NOT_FOUND);
+
+    assert!(handler.reject(anyhow::anyhow!("network down")).is_err());
+    assert_eq!(expect_close_code(&mut ws_rx), close_code::ERROR);
+  }
+
   #[tokio::test]
   async fn test_handle_sdk_play_with_start_time_sends_command() {
     let (mut handler, mut command_rx, _ws_rx) = create_test_handler();
@@ -306,6 +306,11 @@ impl FeedbackHandler {
           Message::Binary(_) => {
             warn!("V3 binary messages not supported for encryption");
           }
+          Message::Close(frame) => {
+            if let Err(e) = ws_sender.send(Message::Close(frame)) {
+              error!("Failed to forward V3 close message: {}", e);
+            }
+          }
           _ => {
             debug!("Ignoring non-content V3 message type");
           }
@@ -316,3 +321,123 @@ impl FeedbackHandler {
     });
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::server::InMemoryDefinitionsProvider;
+  use bh_haptic_definitions::HapticDefinitionsMessage;
+  use bh_sdk::v3::ServerMessage;
+  use rsa::Pkcs1v15Encrypt;
+  use std::sync::Arc;
+
+  /// Handler wrapping a v3 handler with the `definitions`, and the public key of the server.
+  async fn create_test_handler(
+    definitions: InMemoryDefinitionsProvider,
+  ) -> (
+    FeedbackHandler,
+    RsaPublicKey,
+    mpsc::Receiver<HapticManagerCommand>,
+    mpsc::UnboundedReceiver<Message>,
+  ) {
+    let app_ctx = AppContext {
+      workspace_id: "test-workspace".to_string(),
+      api_key: "test-api-key".to_string(),
+      version: Some("1.0.0".to_string()),
+      device_id: None,
+    };
+    let (command_tx, command_rx) = mpsc::channel(10);
+    let (ws_tx, ws_rx) = mpsc::unbounded_channel();
+    let (v3_message_tx, v3_message_rx) = mpsc::unbounded_channel();
+
+    let v3_handler =
+      v3::FeedbackHandlerBuilder::new((&app_ctx).into(), command_tx.clone(), v3_message_tx)
+        .with_definitions(Arc::new(definitions))
+        .build()
+        .await
+        .unwrap();
+
+    // small deterministic key, generating a 2048 bit key is slow
+    let mut rng = ChaCha20Rng::from_seed([42u8; 32]);
+    let private_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+    let public_key = RsaPublicKey::from(&private_key);
+
+    let handler = FeedbackHandlerBuilder::new(app_ctx, command_tx, ws_tx)
+      .with_v3_handler(v3_handler)
+      .with_v3_message_receiver(v3_message_rx)
+      .with_private_key(Some(private_key))
+      .build()
+      .await
+      .unwrap();
+
+    (handler, public_key, command_rx, ws_rx)
+  }
+
+  fn create_client_key_message(public_key: &RsaPublicKey) -> String {
+    let encrypted_key = public_key
+      .encrypt(&mut rand::rng(), Pkcs1v15Encrypt, &[7u8; 32])
+      .unwrap();
+    let msg = SdkEncryptedMessage::sdk_client_key(STANDARD.encode(encrypted_key));
+
+    serde_json::to_string(&msg).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_handshake_registers_provided_definitions() {
+    let definitions = HapticDefinitionsMessage::new(vec![]).with_id(Some("test-id".to_string()));
+    let provider = InMemoryDefinitionsProvider::new()
+      .with_workspace("test-workspace".to_string(), definitions.clone());
+    let (mut handler, public_key, mut command_rx, mut ws_rx) = create_test_handler(provider).await;
+
+    let result = handler
+      .handle_text_message(&create_client_key_message(&public_key))
+      .await;
+    assert!(result.is_ok());
+
+    assert!(matches!(
+      command_rx.recv().await.unwrap(),
+      HapticManagerCommand::ClientConnected { .. }
+    ));
+    match command_rx.recv().await.unwrap() {
+      HapticManagerCommand::RegisterHapticDefinitions {
+        namespace,
+        definitions: received_defs,
+      } => {
+        assert_eq!(namespace, "test-workspace");
+        assert_eq!(*received_defs, definitions);
+      }
+      _ => panic!("Expected RegisterHapticDefinitions command"),
+    }
+
+    // the v3 messages reach the client encrypted
+    let Message::Text(text) = ws_rx.recv().await.unwrap() else {
+      panic!("Expected text message");
+    };
+    let Ok(SdkEncryptedMessage::SdkData { data }) = serde_json::from_str(&text) else {
+      panic!("Expected SdkData message");
+    };
+    let parsed: ServerMessage =
+      serde_json::from_str(&handler.crypto.decrypt_aes_gcm(&data).unwrap()).unwrap();
+    assert!(matches!(parsed, ServerMessage::ServerReady));
+  }
+
+  #[tokio::test]
+  async fn test_handshake_closes_connection_of_unknown_workspace() {
+    let (mut handler, public_key, mut command_rx, mut ws_rx) =
+      create_test_handler(InMemoryDefinitionsProvider::new()).await;
+
+    let result = handler
+      .handle_text_message(&create_client_key_message(&public_key))
+      .await;
+    assert!(result.is_err());
+
+    match ws_rx.recv().await.unwrap() {
+      Message::Close(Some(frame)) => assert_eq!(frame.code, v3::CLOSE_WORKSPACE_NOT_FOUND),
+      _ => panic!("Expected close message"),
+    }
+    assert!(
+      command_rx.try_recv().is_err(),
+      "Should not register the client"
+    );
+  }
+}
@@ -25,6 +25,11 @@ use tracing::*;
 mod config;
 pub(crate) mod handlers;
 
+#[cfg(feature = "v3")]
+use crate::server::{
+  ChainedDefinitionsProvider, CloudDefinitionsProvider, DefinitionsProvider,
+  LocalDefinitionsProvider,
+};
 use crate::server::{HapticManagerCommand, HapticManagerEvent};
 pub use config::*;
 pub use handlers::{HandlerBuilder, MessageHandler};
@@ -35,6 +40,20 @@ pub struct AppState {
   command_sender: mpsc::Sender<HapticManagerCommand>,
   event_sender: broadcast::Sender<HapticManagerEvent>,
   cancellation_token: CancellationToken,
+
+  #[cfg(feature = "v3")]
+  definitions: Arc<dyn DefinitionsProvider>,
+}
+
+#[cfg(feature = "v3")]
+fn default_definitions_provider(config: &BhWebsocketServerConfig) -> Arc<dyn DefinitionsProvider> {
+  let mut provider = ChainedDefinitionsProvider::new();
+  if let Some(local) = config.local_definitions() {
+    provider = provider.then(LocalDefinitionsProvider::new(local.clone()));
+  }
+  let provider = provider.then(CloudDefinitionsProvider::new(config.definitions().clone()));
+
+  Arc::new(provider)
 }
 
 async fn kickstart_ws(socket: &mut WebSocket) -> Result<(), axum::Error> {
@@ -74,9 +93,35 @@ impl<H: MessageHandler> HandlerBuildStrategy<H> for StandardHandlerStrategy {
   }
 }
 
+/// V3 strategy: build the handler with the configured definitions provider
+#[cfg(feature = "v3")]
+struct V3HandlerStrategy {
+  definitions: Arc<dyn DefinitionsProvider>,
+}
+
+#[cfg(feature = "v3")]
+#[async_trait]
+impl HandlerBuildStrategy<handlers::v3::FeedbackHandler> for V3HandlerStrategy {
+  async fn build_handler(
+    &self,
+    context: handlers::v3::AppContext,
+    command_tx: mpsc::Sender<HapticManagerCommand>,
+    ws_tx: mpsc::UnboundedSender<Message>,
+    token: CancellationToken,
+  ) -> anyhow::Result<handlers::v3::FeedbackHandler> {
+    handlers::v3::FeedbackHandlerBuilder::new(context, command_tx, ws_tx)
+      .with_definitions(self.definitions.clone())
+      .with_cancellation_token(token)
+      .build()
+      .await
+  }
+}
+
 /// V4 Composition strategy: build V3 externally, then wrap in V4
 #[cfg(feature = "v4")]
-struct V4CompositionStrategy;
+struct V4CompositionStrategy {
+  definitions: Arc<dyn DefinitionsProvider>,
+}
 
 #[cfg(feature = "v4")]
 #[async_trait]
@@ -100,6 +145,7 @@ impl HandlerBuildStrategy<handlers::v4::FeedbackHandler> for V4CompositionStrate
       command_tx.clone(),
       v3_message_tx, // V3 messages will be captured here
     )
+    .with_definitions(self.definitions.clone())
     .with_cancellation_token(token.clone())
     .build()
     .await?;
@@ -294,6 +340,25 @@ async fn upgrade_websocket_standard<H: MessageHandler>(
   .await
 }
 
+/// V3 WebSocket upgrade handler using the configured definitions provider
+#[cfg(feature = "v3")]
+async fn upgrade_websocket_v3(
+  ws: WebSocketUpgrade,
+  Query(context): Query<handlers::v3::AppContext>,
+  State(app_state): State<AppState>,
+) -> axum::response::Response {
+  let strategy = V3HandlerStrategy {
+    definitions: app_state.definitions.clone(),
+  };
+  upgrade_websocket_with_strategy::<handlers::v3::FeedbackHandler, V3HandlerStrategy>(
+    ws,
+    Query(context),
+    State(app_state),
+    strategy,
+  )
+  .await
+}
+
 /// V4 WebSocket upgrade handler using the composition strategy
 #[cfg(feature = "v4")]
 async fn upgrade_websocket_v4_composition(
@@ -301,11 +366,14 @@ async fn upgrade_websocket_v4_composition(
   Query(context): Query<handlers::v4::AppContext>,
   State(app_state): State<AppState>,
 ) -> axum::response::Response {
+  let strategy = V4CompositionStrategy {
+    definitions: app_state.definitions.clone(),
+  };
   upgrade_websocket_with_strategy::<handlers::v4::FeedbackHandler, V4CompositionStrategy>(
     ws,
     Query(context),
     State(app_state),
-    V4CompositionStrategy,
+    strategy,
   )
   .await
 }
@@ -322,6 +390,12 @@ pub struct BhWebsocketServerBuilder {
 
   #[getset(set_with = "pub")]
   cancellation_token: Option<CancellationToken>,
+
+  /// Source of the haptic definitions of the v3/v4 clients, by default the local definitions of
+  /// the config, if any, and then its definitions client.
+  #[cfg(feature = "v3")]
+  #[getset(set_with = "pub")]
+  definitions_provider: Option<Arc<dyn DefinitionsProvider>>,
 }
 
 impl BhWebsocketServerBuilder {
@@ -339,6 +413,9 @@ impl BhWebsocketServerBuilder {
       tls_config: None,
 
       cancellation_token: None,
+
+      #[cfg(feature = "v3")]
+      definitions_provider: None,
     }
   }
 
@@ -383,11 +460,19 @@ impl BhWebsocketServerBuilder {
 
     let cancellation_token = self.cancellation_token.unwrap_or_default();
 
+    #[cfg(feature = "v3")]
+    let definitions = self
+      .definitions_provider
+      .unwrap_or_else(|| default_definitions_provider(&self.config));
+
     // Create version-specific upgrade handlers using State pattern
     let app_state = AppState {
       command_sender: self.command_sender,
       event_sender: self.event_sender,
       cancellation_token: cancellation_token.clone(),
+
+      #[cfg(feature = "v3")]
+      definitions,
     };
 
     let mut app = Router::new();
@@ -423,14 +508,8 @@ impl BhWebsocketServerBuilder {
     #[cfg(feature = "v3")]
     {
       app = app
-        .route(
-          "/v3/feedback",
-          any(upgrade_websocket_standard::<handlers::v3::FeedbackHandler>),
-        )
-        .route(
-          "/v3/feedback/",
-          any(upgrade_websocket_standard::<handlers::v3::FeedbackHandler>),
-        );
+        .route("/v3/feedback", any(upgrade_websocket_v3))
+        .route("/v3/feedback/", any(upgrade_websocket_v3));
     }
 
     #[cfg(feature = "v4")]